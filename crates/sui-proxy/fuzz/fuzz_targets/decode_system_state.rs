@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sui_proxy::peers::decode_system_state_response;
+
+// exercises the json-rpc decode path `SuiNodeProvider::get_validators` runs once it has a raw
+// response body in hand (`decode_system_state_response`), with arbitrary/adversarial bytes: a
+// compromised or buggy full node is the realistic source of this input. the only contract is
+// "never panic" -- every malformed-input case is expected to come back as an `Err`.
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_system_state_response(data, false);
+    let _ = decode_system_state_response(data, true);
+});
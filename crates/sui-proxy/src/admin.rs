@@ -1,12 +1,15 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::config::{PeerValidationConfig, RemoteWriteConfig};
-use crate::handlers::publish_metrics;
+use crate::consumer::ForwardingGate;
+use crate::handlers::{
+    get_system_state, health, poll_diagnostic, publish_metrics, readyz, stream_peer_changes,
+};
 use crate::middleware::{expect_mysten_proxy_header, expect_valid_public_key};
 use crate::peers::SuiNodeProvider;
 use anyhow::Result;
 
-use axum::routing::post as axum_post;
+use axum::routing::{get as axum_get, post as axum_post};
 use axum::Extension;
 use axum::{middleware, Router};
 use fastcrypto::ed25519::{Ed25519KeyPair, Ed25519PublicKey};
@@ -83,21 +86,76 @@ pub fn make_reqwest_client(settings: RemoteWriteConfig) -> ReqwestClient {
     }
 }
 
-/// App will configure our routes. This fn is also used to instrument our tests
-pub fn app(network: String, client: ReqwestClient, allower: Option<SuiNodeProvider>) -> Router {
+/// ReadinessGate backs the `/readyz` route: whether `provider`'s allow list has reached
+/// `min_peers`, via `SuiNodeProvider::wait_ready`, for use as a Kubernetes readiness probe that
+/// shouldn't route traffic to this pod until it knows enough of the committee to serve it. A
+/// proxy running without a provider at all (no peer-cert enforcement configured) or without
+/// `min_peers` configured is always ready, since there's no allow list it can or must wait on.
+#[derive(Clone)]
+pub struct ReadinessGate {
+    provider: Option<Arc<SuiNodeProvider>>,
+    min_peers: Option<usize>,
+}
+
+impl ReadinessGate {
+    pub fn new(provider: Option<Arc<SuiNodeProvider>>, min_peers: Option<usize>) -> Self {
+        Self { provider, min_peers }
+    }
+
+    /// is_ready checks the threshold without blocking the request: `wait_ready` with a zero
+    /// timeout resolves immediately, either because the threshold is already met or because the
+    /// zero-duration wait elapses right away.
+    pub(crate) async fn is_ready(&self) -> bool {
+        match (&self.provider, self.min_peers) {
+            (Some(provider), Some(min_peers)) => provider
+                .wait_ready(min_peers, Duration::ZERO)
+                .await
+                .is_ok(),
+            _ => true,
+        }
+    }
+}
+
+/// App will configure our routes. This fn is also used to instrument our tests. `forwarding`
+/// gates the `/publish/metrics` route: see `ForwardingGate::pause`. `readiness_min_peers` backs
+/// the `/readyz` route: see `ReadinessGate`.
+pub fn app(
+    network: String,
+    client: ReqwestClient,
+    allower: Option<SuiNodeProvider>,
+    forwarding: ForwardingGate,
+    readiness_min_peers: Option<usize>,
+) -> Router {
+    let allower = allower.map(Arc::new);
+    let readiness = ReadinessGate::new(allower.clone(), readiness_min_peers);
+
+    // the health and readiness routes are reachable without a client certificate at all (see
+    // `create_server_cert_enforce_peer`'s `ClientAuthPolicy::Optional`), so they're kept outside
+    // the `expect_valid_public_key`/`expect_mysten_proxy_header` layers the other routes go
+    // through.
+    let health_router = Router::new()
+        .route("/health", axum_get(health))
+        .route("/readyz", axum_get(readyz))
+        .layer(Extension(readiness));
+
     // build our application with a route and our sender mpsc
     let mut router = Router::new()
         .route("/publish/metrics", axum_post(publish_metrics))
+        .route("/system-state", axum_get(get_system_state))
+        .route("/peer-changes", axum_get(stream_peer_changes))
+        .route("/admin/poll-diagnostic", axum_get(poll_diagnostic))
         .route_layer(middleware::from_fn(expect_mysten_proxy_header));
 
     if let Some(allower) = allower {
         router = router
             .route_layer(middleware::from_fn(expect_valid_public_key))
-            .layer(Extension(Arc::new(allower)));
+            .layer(Extension(allower));
     }
-    router
+    health_router
+        .merge(router)
         .layer(Extension(network))
         .layer(Extension(client))
+        .layer(Extension(forwarding))
         .layer(
             ServiceBuilder::new().layer(
                 TraceLayer::new_for_http().on_response(
@@ -191,18 +249,204 @@ pub fn create_server_cert_default_allow(
 }
 
 /// Verify clients against sui blockchain, clients that are not found in sui_getValidators
-/// will be rejected
+/// will be rejected. Callers are responsible for calling `SuiNodeProvider::poll_peer_list`
+/// themselves once they're done wiring up metrics, so that the allow list isn't refreshed before
+/// a metrics registry is attached to it.
 pub fn create_server_cert_enforce_peer(
     peer_config: PeerValidationConfig,
 ) -> Result<(ServerConfig, Option<SuiNodeProvider>), sui_tls::rustls::Error> {
     let (Some(certificate_path), Some(private_key_path)) = (peer_config.certificate_file, peer_config.private_key) else {
         return Err(sui_tls::rustls::Error::General("missing certs to initialize server".into()));
     };
-    let allower = SuiNodeProvider::new(peer_config.url, peer_config.interval);
-    allower.poll_peer_list();
-    let c = CertVerifier::new(allower.clone()).rustls_server_config(
-        load_certs(&certificate_path),
-        load_private_key(&private_key_path),
-    )?;
+    let mut allower = SuiNodeProvider::new(peer_config.url, peer_config.interval);
+    allower.set_jsonrpc_version(peer_config.jsonrpc_version);
+    if let Some(outbound_proxy_url) = peer_config.outbound_proxy_url {
+        allower.set_outbound_proxy(outbound_proxy_url, peer_config.outbound_no_proxy);
+    }
+    if let Some(discovery_url) = peer_config.discovery_url {
+        let discovery_interval = peer_config
+            .discovery_interval
+            .expect("discovery_interval is required when discovery_url is set");
+        allower.set_discovery_source(discovery_url, discovery_interval);
+    }
+    if let Some(geoip_database) = peer_config.geoip_database {
+        allower
+            .set_geoip_database(std::path::Path::new(&geoip_database))
+            .expect("unable to load geoip database");
+    }
+    if !peer_config.excluded_countries.is_empty() {
+        allower.set_excluded_countries(peer_config.excluded_countries);
+    }
+    if peer_config.prefer_http2 {
+        allower.set_prefer_http2(true);
+    }
+    if !peer_config.force_allow_peers.is_empty() || !peer_config.force_deny_peers.is_empty() {
+        let mut overrides = std::collections::HashMap::new();
+        for hex in peer_config.force_allow_peers {
+            let key = crate::peers::parse_ed25519_hex(&hex)
+                .expect("invalid key in force_allow_peers");
+            overrides.insert(key, crate::peers::PeerOverride::ForceAllow);
+        }
+        for hex in peer_config.force_deny_peers {
+            let key = crate::peers::parse_ed25519_hex(&hex)
+                .expect("invalid key in force_deny_peers");
+            overrides.insert(key, crate::peers::PeerOverride::ForceDeny);
+        }
+        allower.set_peer_overrides(overrides);
+    }
+    if let Some(webhook_url) = peer_config.webhook_url {
+        let batch_window = peer_config
+            .webhook_batch_window
+            .expect("webhook_batch_window is required when webhook_url is set");
+        allower.set_webhook(
+            webhook_url,
+            batch_window,
+            peer_config.webhook_max_retries,
+            peer_config.webhook_retry_backoff,
+        );
+    }
+    if let Some(audit_file_path) = peer_config.audit_file_path {
+        allower.set_audit_file(
+            std::path::PathBuf::from(audit_file_path),
+            peer_config.audit_file_max_size_bytes,
+            peer_config.audit_file_max_age,
+        );
+    }
+    // client auth is optional at the TLS layer so the anonymous `/health` route stays reachable;
+    // every route that actually needs a validator identity enforces that itself via the
+    // `expect_valid_public_key` middleware `app` layers onto it.
+    let c = CertVerifier::new(allower.clone())
+        .with_optional_client_auth()
+        .rustls_server_config(load_certs(&certificate_path), load_private_key(&private_key_path))?;
     Ok((c, Some(allower)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RemoteWriteConfig;
+    use axum::http::{header, StatusCode};
+
+    /// spawns `app` behind a TLS acceptor configured with `ClientAuthPolicy::Optional`, returning
+    /// its base url.
+    async fn spawn_server_with_optional_client_auth(
+        allower: SuiNodeProvider,
+    ) -> (String, SelfSignedCertificate) {
+        let server_keypair = Ed25519KeyPair::generate(&mut rand::thread_rng());
+        let server_certificate = SelfSignedCertificate::new(server_keypair.private(), "localhost");
+        let tls_config = CertVerifier::new(allower.clone())
+            .with_optional_client_auth()
+            .rustls_server_config(
+                vec![server_certificate.rustls_certificate()],
+                server_certificate.rustls_private_key(),
+            )
+            .unwrap();
+
+        let client = make_reqwest_client(RemoteWriteConfig::default());
+        let forwarding = ForwardingGate::new(crate::consumer::ForwardingPausePolicy::Drop);
+        let app = app("unittest-network".into(), client, Some(allower), forwarding, None);
+
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let server_address = listener.local_addr().unwrap();
+        let acceptor = TlsAcceptor::new(tls_config);
+        tokio::spawn(async move {
+            server(listener, app, Some(acceptor)).await.unwrap();
+        });
+
+        (format!("https://localhost:{}", server_address.port()), server_certificate)
+    }
+
+    #[tokio::test]
+    async fn health_route_is_reachable_without_a_client_certificate() {
+        let allower = SuiNodeProvider::new("".into(), Duration::from_secs(30));
+        let (base_url, server_certificate) = spawn_server_with_optional_client_auth(allower).await;
+
+        let anonymous_client = reqwest::Client::builder()
+            .add_root_certificate(server_certificate.reqwest_certificate())
+            .https_only(true)
+            .build()
+            .unwrap();
+
+        let res = anonymous_client
+            .get(format!("{base_url}/health"))
+            .send()
+            .await
+            .expect("expected the health route to be reachable without a client certificate");
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn push_route_rejects_a_missing_or_unknown_client_certificate() {
+        let allower = SuiNodeProvider::new("".into(), Duration::from_secs(30));
+        let (base_url, server_certificate) = spawn_server_with_optional_client_auth(allower).await;
+        let push_url = format!("{base_url}/publish/metrics");
+
+        let anonymous_client = reqwest::Client::builder()
+            .add_root_certificate(server_certificate.reqwest_certificate())
+            .https_only(true)
+            .build()
+            .unwrap();
+        let res = anonymous_client
+            .post(&push_url)
+            .header(header::CONTENT_TYPE, prometheus::PROTOBUF_FORMAT)
+            .body(Vec::new())
+            .send()
+            .await
+            .expect("the TLS handshake itself should still succeed without a certificate");
+        assert_eq!(
+            res.status(),
+            StatusCode::UNAUTHORIZED,
+            "the push route should reject a request with no client certificate at all"
+        );
+
+        let CertKeyPair(unknown_cert, _) = generate_self_cert("sui".into());
+        let unknown_client = reqwest::Client::builder()
+            .add_root_certificate(server_certificate.reqwest_certificate())
+            .identity(unknown_cert.reqwest_identity())
+            .https_only(true)
+            .build()
+            .unwrap();
+        unknown_client
+            .post(&push_url)
+            .header(header::CONTENT_TYPE, prometheus::PROTOBUF_FORMAT)
+            .body(Vec::new())
+            .send()
+            .await
+            .expect_err("a certificate for a key not in the allow list should fail the TLS handshake itself");
+    }
+
+    #[tokio::test]
+    async fn readiness_gate_is_ready_without_a_provider_or_without_a_configured_threshold() {
+        assert!(ReadinessGate::new(None, Some(1)).is_ready().await);
+
+        let provider = Arc::new(SuiNodeProvider::new("".into(), Duration::from_secs(30)));
+        assert!(ReadinessGate::new(Some(provider), None).is_ready().await);
+    }
+
+    #[tokio::test]
+    async fn readiness_gate_reflects_whether_the_allow_list_has_reached_min_peers() {
+        let provider = SuiNodeProvider::new("".into(), Duration::from_secs(30));
+        let gate = ReadinessGate::new(Some(Arc::new(provider.clone())), Some(1));
+
+        assert!(!gate.is_ready().await, "an empty allow list shouldn't be ready yet");
+
+        let CertKeyPair(_, pub_key) = generate_self_cert("sui".into());
+        provider.seed_peers(vec![crate::peers::SuiPeer {
+            name: "some-node".into(),
+            raw_name: "some-node".into(),
+            p2p_address: multiaddr::Multiaddr::empty(),
+            p2p_addresses: vec![multiaddr::Multiaddr::empty()],
+            public_key: pub_key,
+            voting_power: 0,
+            pending_removal: false,
+            pending_governance: false,
+            no_dial: false,
+            additional_keys: Vec::new(),
+            sui_address: "0x0".into(),
+            geo: None,
+            registry_metadata: None,
+        }]);
+
+        assert!(gate.is_ready().await, "the allow list should be ready once min_peers is reached");
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use crate::relabel::RelabelRule;
 use anyhow::{Context, Result};
 use core::time::Duration;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -34,13 +35,29 @@ pub struct RemoteWriteConfig {
     /// <https://docs.rs/reqwest/latest/reqwest/struct.ClientBuilder.html#method.pool_max_idle_per_host>
     #[serde(default = "pool_max_idle_per_host_default")]
     pub pool_max_idle_per_host: usize,
+
+    /// when set, every relayed metric name is prefixed with this pattern, with `{name}`
+    /// substituted for the reporting validator's name (see
+    /// `consumer::metric_namespace_for_peer_name`); e.g. `validator_{name}_` turns `uptime` into
+    /// `validator_node-a_uptime`. `None` (the default) applies no namespacing, preserving the
+    /// historical behavior.
+    #[serde(default)]
+    pub metric_namespace_pattern: Option<String>,
+
+    /// Prometheus-style relabel rules applied in order to every relayed metric, using the
+    /// `host`/`network` labels `consumer::populate_labels` attaches from the reporting peer (see
+    /// `relabel::apply_relabel_rules`). Defaults to empty, the historical no-op behavior.
+    #[serde(default)]
+    pub relabel_rules: Vec<RelabelRule>,
 }
 
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct PeerValidationConfig {
-    /// url is the json-rpc url we use to obtain valid peers on the blockchain
+    /// url is the json-rpc url we use to obtain valid peers on the blockchain.  Accepts plain
+    /// HTTP(S) urls, or a `unix:///path/to.sock` url to poll a co-located full node over a Unix
+    /// domain socket.
     pub url: String,
     #[serde_as(as = "DurationSeconds<u64>")]
     pub interval: Duration,
@@ -55,6 +72,106 @@ pub struct PeerValidationConfig {
     /// private key for tls
     /// please use an absolute path
     pub private_key: Option<String>,
+
+    /// the "jsonrpc" version string sent in requests to `url`; defaults to "2.0" but some
+    /// compatibility gateways expect "1.0" or no version field at all
+    #[serde(default = "jsonrpc_version_default")]
+    pub jsonrpc_version: String,
+
+    /// an HTTP/SOCKS egress proxy to route polling requests through, for topologies where `url`
+    /// is only reachable via a proxy. Accepts the schemes `reqwest::Proxy::all` understands
+    /// (`http://`, `https://`, `socks5://`).
+    pub outbound_proxy_url: Option<String>,
+    /// a comma-separated list of hosts that should bypass `outbound_proxy_url`, matching the
+    /// conventional `NO_PROXY` environment variable semantics.
+    pub outbound_no_proxy: Option<String>,
+
+    /// a JSON service-registry endpoint resolved on `discovery_interval` to obtain the current
+    /// set of full-node rpc endpoints, used in place of `url` once resolved. See
+    /// `peers::SuiNodeProvider::set_discovery_source`. `url` is still required and used as the
+    /// fallback until the first successful resolution.
+    pub discovery_url: Option<String>,
+    /// how often `discovery_url` is re-resolved; required when `discovery_url` is set.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(default)]
+    pub discovery_interval: Option<Duration>,
+
+    /// prefix applied to every allow-list Prometheus metric in place of the historical
+    /// `sui_validator_` prefix; defaults to `sui_proxy_peers_` so multiple proxy deployments can
+    /// report into one shared Prometheus store without colliding. See
+    /// `peers::MetricNamingConfig`.
+    #[serde(default = "metric_prefix_default")]
+    pub metric_prefix: String,
+    /// const labels (e.g. `{network = "mainnet"}`) attached to every allow-list Prometheus
+    /// metric, to distinguish series from different proxy deployments sharing one store.
+    #[serde(default)]
+    pub metric_const_labels: std::collections::HashMap<String, String>,
+
+    /// path to a country/ASN enrichment database (see `peers::GeoInfo`) consulted on every poll
+    /// to populate each peer's country/ASN and label metrics by country. Optional; when unset,
+    /// no enrichment happens and `excluded_countries` has no effect.
+    pub geoip_database: Option<String>,
+    /// ISO country codes to reject validators for, once enriched via `geoip_database`; requires
+    /// `geoip_database` to also be set.
+    #[serde(default)]
+    pub excluded_countries: std::collections::HashSet<String>,
+
+    /// when true, outbound RPC polling connections prefer HTTP/2; see
+    /// `peers::SuiNodeProvider::set_prefer_http2` for how this interacts with `https://` vs
+    /// `http://` endpoints. Defaults to `false`.
+    #[serde(default)]
+    pub prefer_http2: bool,
+
+    /// hex-encoded ed25519 public keys (see `peers::parse_ed25519_hex`) to always admit,
+    /// regardless of whether the polled committee currently reports them. See
+    /// `peers::SuiNodeProvider::set_peer_overrides`.
+    #[serde(default)]
+    pub force_allow_peers: Vec<String>,
+    /// hex-encoded ed25519 public keys to always reject, even if the polled committee currently
+    /// reports them. Takes precedence over `force_allow_peers` for a key listed in both.
+    #[serde(default)]
+    pub force_deny_peers: Vec<String>,
+
+    /// a url to POST batches of peer-change events to as JSON, with retry/backoff and a
+    /// dead-letter log on persistent failure. See `peers::SuiNodeProvider::set_webhook`. Optional;
+    /// when unset, no webhook task runs.
+    pub webhook_url: Option<String>,
+    /// events recorded within this window of the first one in a batch are coalesced into a
+    /// single POST to `webhook_url`. Required when `webhook_url` is set.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(default)]
+    pub webhook_batch_window: Option<Duration>,
+    /// the number of retries attempted for a batch that fails delivery to `webhook_url` before
+    /// it's moved to the dead-letter log.
+    #[serde(default = "webhook_max_retries_default")]
+    pub webhook_max_retries: u32,
+    /// the delay before the first retry of a failed delivery to `webhook_url`; doubled after
+    /// every subsequent failure, capped at 60s.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "webhook_retry_backoff_default")]
+    pub webhook_retry_backoff: Duration,
+
+    /// path to append a structured JSONL audit file to, one line per peer add/remove/rotation
+    /// (timestamp, epoch, sui_address, hex-encoded key fingerprint), durable beyond the in-memory
+    /// audit log. See `peers::SuiNodeProvider::set_audit_file`. Optional; when unset, no audit
+    /// file task runs.
+    pub audit_file_path: Option<String>,
+    /// once the audit file reaches this size, it's rotated to `<audit_file_path>.<unix
+    /// timestamp>` and a fresh file started. Unset disables size-based rotation.
+    #[serde(default)]
+    pub audit_file_max_size_bytes: Option<u64>,
+    /// once the audit file has been open longer than this (since the proxy opened it or last
+    /// rotated it), it's rotated the same way size-based rotation does. Unset disables age-based
+    /// rotation.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(default)]
+    pub audit_file_max_age: Option<Duration>,
+
+    /// the allow list must reach at least this many peers before the `/readyz` route reports
+    /// ready; see `peers::SuiNodeProvider::wait_ready`. Unset (the default) makes `/readyz`
+    /// always report ready, the historical behavior.
+    #[serde(default)]
+    pub readiness_min_peers: Option<usize>,
 }
 
 /// the default idle worker per host (reqwest to remote write url call)
@@ -67,11 +184,33 @@ fn hostname_default() -> Option<String> {
     Some("localhost".to_string())
 }
 
+/// the default jsonrpc version string we send when polling for peers
+fn jsonrpc_version_default() -> String {
+    "2.0".to_string()
+}
+
 /// the default remote write url
 fn remote_write_url() -> String {
     "http://metrics-gw.testnet.sui.io/api/v1/push".to_string()
 }
 
+/// the default prefix for allow-list Prometheus metrics; see `PeerValidationConfig::metric_prefix`
+fn metric_prefix_default() -> String {
+    "sui_proxy_peers_".to_string()
+}
+
+/// the default number of retries for a failed webhook delivery; see
+/// `PeerValidationConfig::webhook_max_retries`
+fn webhook_max_retries_default() -> u32 {
+    3
+}
+
+/// the default initial backoff before retrying a failed webhook delivery; see
+/// `PeerValidationConfig::webhook_retry_backoff`
+fn webhook_retry_backoff_default() -> Duration {
+    Duration::from_secs(1)
+}
+
 /// load our config file from a path
 pub fn load<P: AsRef<std::path::Path>, T: DeserializeOwned + Serialize>(path: P) -> Result<T> {
     let path = path.as_ref();
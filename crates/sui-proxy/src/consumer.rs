@@ -3,6 +3,7 @@
 
 use crate::admin::ReqwestClient;
 use crate::prom_to_mimir::Mimir;
+use crate::relabel::apply_relabel_rules;
 use crate::remote_write::WriteRequest;
 use anyhow::Result;
 use axum::body::Bytes;
@@ -11,9 +12,13 @@ use bytes::buf::Reader;
 use fastcrypto::ed25519::Ed25519PublicKey;
 use multiaddr::Multiaddr;
 use prometheus::proto::{self, MetricFamily};
+use prometheus::{IntCounter, Opts, Registry};
 use prost::Message;
 use protobuf::CodedInputStream;
+use std::collections::VecDeque;
 use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tracing::{debug, error};
 
 /// NodeMetric holds metadata and a metric payload from the calling node
@@ -26,6 +31,118 @@ pub struct NodeMetric {
     pub data: Vec<MetricFamily>,      // decoded protobuf of prometheus data
 }
 
+/// ForwardingPausePolicy controls what happens to a push handled through `ForwardingGate::admit`
+/// while forwarding is paused: either held in memory for the caller to replay once resumed
+/// (oldest pushes dropped first once `capacity` is reached), or dropped immediately.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForwardingPausePolicy {
+    Buffer { capacity: usize },
+    Drop,
+}
+
+/// ForwardingMetrics counts what `ForwardingGate::admit` did to pushes received while forwarding
+/// was paused, so operators can tell a quiet `/publish/metrics` route apart from one silently
+/// dropping every push.
+#[derive(Clone)]
+struct ForwardingMetrics {
+    dropped_while_paused: IntCounter,
+    buffered_while_paused: IntCounter,
+}
+
+impl ForwardingMetrics {
+    fn new(registry: &Registry) -> Self {
+        let dropped_while_paused = IntCounter::with_opts(Opts::new(
+            "sui_proxy_forwarding_dropped_while_paused_total",
+            "number of incoming pushes dropped because forwarding was paused, either by the Drop policy or because the Buffer policy's capacity was exceeded",
+        ))
+        .unwrap();
+        registry.register(Box::new(dropped_while_paused.clone())).unwrap();
+
+        let buffered_while_paused = IntCounter::with_opts(Opts::new(
+            "sui_proxy_forwarding_buffered_while_paused_total",
+            "number of incoming pushes buffered because forwarding was paused under the Buffer policy",
+        ))
+        .unwrap();
+        registry.register(Box::new(buffered_while_paused.clone())).unwrap();
+
+        Self { dropped_while_paused, buffered_while_paused }
+    }
+}
+
+/// ForwardingGate lets an operator pause relaying metrics to the upstream remote_write endpoint
+/// (e.g. during a storage incident) independently of `peers::SuiNodeProvider::pause`, so the
+/// allow list keeps tracking committee changes while downstream writes are held back. Every push
+/// handled through `admit` while paused is either buffered (returned in order by `resume`, for the
+/// caller to forward) or dropped, per `policy`. See `pause`/`resume`.
+#[derive(Clone)]
+pub struct ForwardingGate {
+    paused: Arc<AtomicBool>,
+    policy: ForwardingPausePolicy,
+    buffered: Arc<Mutex<VecDeque<NodeMetric>>>,
+    metrics: Option<ForwardingMetrics>,
+}
+
+impl ForwardingGate {
+    pub fn new(policy: ForwardingPausePolicy) -> Self {
+        Self {
+            paused: Arc::new(AtomicBool::new(false)),
+            policy,
+            buffered: Arc::new(Mutex::new(VecDeque::new())),
+            metrics: None,
+        }
+    }
+
+    pub fn set_metrics(&mut self, registry: &Registry) {
+        self.metrics = Some(ForwardingMetrics::new(registry));
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// resume clears the paused flag and drains whatever was buffered while paused, returned in
+    /// the order it was received, for the caller to forward now that writes are no longer held
+    /// back. A no-op beyond clearing the flag under `ForwardingPausePolicy::Drop`, since nothing
+    /// was ever buffered.
+    pub fn resume(&self) -> Vec<NodeMetric> {
+        self.paused.store(false, Ordering::SeqCst);
+        self.buffered.lock().unwrap().drain(..).collect()
+    }
+
+    /// admit returns `node_metric` back to the caller to forward immediately unless forwarding is
+    /// currently paused, in which case it's consumed per `policy` and `None` is returned.
+    pub fn admit(&self, node_metric: NodeMetric) -> Option<NodeMetric> {
+        if !self.is_paused() {
+            return Some(node_metric);
+        }
+        match &self.policy {
+            ForwardingPausePolicy::Drop => {
+                if let Some(metrics) = &self.metrics {
+                    metrics.dropped_while_paused.inc();
+                }
+            }
+            ForwardingPausePolicy::Buffer { capacity } => {
+                let mut buffered = self.buffered.lock().unwrap();
+                if buffered.len() >= *capacity {
+                    buffered.pop_front();
+                    if let Some(metrics) = &self.metrics {
+                        metrics.dropped_while_paused.inc();
+                    }
+                }
+                buffered.push_back(node_metric);
+                if let Some(metrics) = &self.metrics {
+                    metrics.buffered_while_paused.inc();
+                }
+            }
+        }
+        None
+    }
+}
+
 /// The ProtobufDecoder will decode message delimited protobuf messages from prom_model.proto types
 /// They are delimited by size, eg a format is such:
 /// []byte{size, data, size, data, size, data}, etc etc
@@ -77,6 +194,31 @@ fn populate_labels(node_metric: NodeMetric) -> Vec<MetricFamily> {
     data
 }
 
+/// metric_namespace_for_peer_name derives the metric-name prefix to apply for a validator, given
+/// `RemoteWriteConfig::metric_namespace_pattern`. `pattern` is expected to contain the literal
+/// token `{name}`, substituted with `peer_name`; a pattern without that token is used verbatim as
+/// a fixed prefix. Returns an empty string (no namespacing) when `pattern` is `None`, the safe
+/// default.
+fn metric_namespace_for_peer_name(pattern: Option<&str>, peer_name: &str) -> String {
+    match pattern {
+        Some(pattern) => pattern.replace("{name}", peer_name),
+        None => String::new(),
+    }
+}
+
+/// apply_metric_namespace prefixes every metric family's name with `namespace`, leaving the
+/// metrics untouched when `namespace` is empty (the safe default, see
+/// `metric_namespace_for_peer_name`).
+fn apply_metric_namespace(data: &mut [MetricFamily], namespace: &str) {
+    if namespace.is_empty() {
+        return;
+    }
+    for mf in data.iter_mut() {
+        let namespaced = format!("{namespace}{}", mf.get_name());
+        mf.set_name(namespaced);
+    }
+}
+
 fn encode_compress(request: &WriteRequest) -> Result<Vec<u8>, (StatusCode, &'static str)> {
     let mut buf = Vec::new();
     buf.reserve(request.encoded_len());
@@ -150,7 +292,13 @@ pub async fn convert_to_remote_write(
     rc: ReqwestClient,
     node_metric: NodeMetric,
 ) -> (StatusCode, &'static str) {
-    let data = populate_labels(node_metric);
+    let namespace = metric_namespace_for_peer_name(
+        rc.settings.metric_namespace_pattern.as_deref(),
+        &node_metric.host,
+    );
+    let mut data = populate_labels(node_metric);
+    apply_metric_namespace(&mut data, &namespace);
+    apply_relabel_rules(&mut data, &rc.settings.relabel_rules);
     for request in Mimir::from(data) {
         let compressed = match encode_compress(&request) {
             Ok(compressed) => compressed,
@@ -186,3 +334,135 @@ pub async fn convert_to_remote_write(
     }
     (StatusCode::CREATED, "created")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prom_to_mimir::tests::create_metric_family;
+
+    #[test]
+    fn metric_namespace_for_peer_name_substitutes_the_name_token() {
+        assert_eq!(
+            metric_namespace_for_peer_name(Some("validator_{name}_"), "node-a"),
+            "validator_node-a_"
+        );
+    }
+
+    #[test]
+    fn metric_namespace_for_peer_name_defaults_to_no_namespacing() {
+        assert_eq!(metric_namespace_for_peer_name(None, "node-a"), "");
+    }
+
+    #[test]
+    fn apply_metric_namespace_prefixes_every_metric_family_name() {
+        let mut data = vec![create_metric_family(
+            "uptime",
+            "help text",
+            None,
+            Default::default(),
+        )];
+
+        apply_metric_namespace(&mut data, "validator_node-a_");
+
+        assert_eq!(data[0].get_name(), "validator_node-a_uptime");
+    }
+
+    #[test]
+    fn apply_metric_namespace_is_a_no_op_for_an_empty_namespace() {
+        let mut data = vec![create_metric_family(
+            "uptime",
+            "help text",
+            None,
+            Default::default(),
+        )];
+
+        apply_metric_namespace(&mut data, "");
+
+        assert_eq!(data[0].get_name(), "uptime");
+    }
+
+    fn sample_node_metric(host: &str) -> NodeMetric {
+        use fastcrypto::traits::KeyPair;
+
+        NodeMetric {
+            host: host.into(),
+            network: "unittest-network".into(),
+            peer_addr: Multiaddr::empty(),
+            public_key: fastcrypto::ed25519::Ed25519KeyPair::generate(&mut rand::thread_rng())
+                .public()
+                .to_owned(),
+            data: vec![],
+        }
+    }
+
+    #[test]
+    fn admit_returns_the_push_straight_back_while_forwarding_is_not_paused() {
+        let gate = ForwardingGate::new(ForwardingPausePolicy::Drop);
+
+        let admitted = gate.admit(sample_node_metric("node-a"));
+
+        assert!(admitted.is_some(), "an unpaused gate should never hold a push back");
+    }
+
+    #[test]
+    fn admit_drops_every_push_while_paused_under_the_drop_policy() {
+        let registry = Registry::new();
+        let mut gate = ForwardingGate::new(ForwardingPausePolicy::Drop);
+        gate.set_metrics(&registry);
+        gate.pause();
+
+        assert!(gate.admit(sample_node_metric("node-a")).is_none());
+        assert!(gate.admit(sample_node_metric("node-b")).is_none());
+
+        let families = registry.gather();
+        let dropped = families
+            .iter()
+            .find(|f| f.get_name() == "sui_proxy_forwarding_dropped_while_paused_total")
+            .expect("expected the dropped-while-paused counter to be registered");
+        assert_eq!(dropped.get_metric()[0].get_counter().get_value(), 2.0);
+
+        assert!(
+            gate.resume().is_empty(),
+            "nothing should have been buffered under the Drop policy"
+        );
+        assert!(!gate.is_paused());
+        assert!(
+            gate.admit(sample_node_metric("node-c")).is_some(),
+            "a push after resume should be forwarded immediately again"
+        );
+    }
+
+    #[test]
+    fn admit_buffers_pushes_up_to_capacity_then_drops_the_oldest_under_the_buffer_policy() {
+        let registry = Registry::new();
+        let mut gate = ForwardingGate::new(ForwardingPausePolicy::Buffer { capacity: 2 });
+        gate.set_metrics(&registry);
+        gate.pause();
+
+        assert!(gate.admit(sample_node_metric("node-a")).is_none());
+        assert!(gate.admit(sample_node_metric("node-b")).is_none());
+        // exceeds capacity: node-a should be evicted to make room for node-c
+        assert!(gate.admit(sample_node_metric("node-c")).is_none());
+
+        let families = registry.gather();
+        let buffered = families
+            .iter()
+            .find(|f| f.get_name() == "sui_proxy_forwarding_buffered_while_paused_total")
+            .expect("expected the buffered-while-paused counter to be registered");
+        assert_eq!(buffered.get_metric()[0].get_counter().get_value(), 3.0);
+        let dropped = families
+            .iter()
+            .find(|f| f.get_name() == "sui_proxy_forwarding_dropped_while_paused_total")
+            .expect("expected the dropped-while-paused counter to be registered");
+        assert_eq!(dropped.get_metric()[0].get_counter().get_value(), 1.0);
+
+        let replayed = gate.resume();
+        let hosts: Vec<&str> = replayed.iter().map(|m| m.host.as_str()).collect();
+        assert_eq!(hosts, vec!["node-b", "node-c"], "expected the evicted node-a dropped and the rest replayed in order");
+        assert!(!gate.is_paused());
+        assert!(
+            gate.admit(sample_node_metric("node-d")).is_some(),
+            "a push after resume should be forwarded immediately again"
+        );
+    }
+}
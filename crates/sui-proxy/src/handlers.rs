@@ -1,36 +1,288 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use crate::admin::ReqwestClient;
-use crate::consumer::{convert_to_remote_write, NodeMetric};
+use crate::admin::{ReadinessGate, ReqwestClient};
+use crate::consumer::{convert_to_remote_write, ForwardingGate, NodeMetric};
 use crate::middleware::LenDelimProtobuf;
-use crate::peers::SuiPeer;
+use crate::peers::{CachedSystemState, PeerChangeEvent, PollDiagnostic, SuiNodeProvider, SuiPeer};
 use axum::{
     extract::{ConnectInfo, Extension},
     http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
 };
+use futures::StreamExt;
 use multiaddr::Multiaddr;
+use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::wrappers::BroadcastStream;
+
+/// Health check route, reachable without a client certificate (see `admin::app`'s wiring of
+/// `sui_tls::ClientAuthPolicy::Optional`) so it can be used by infrastructure (load balancer
+/// probes, orchestrator liveness checks) that doesn't hold a validator identity.
+pub async fn health() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Readiness probe route, reachable without a client certificate like `health`. Reports
+/// `503 Service Unavailable` until `gate` considers the allow list ready (see
+/// `admin::ReadinessGate`), so a Kubernetes readiness probe can hold traffic back from this pod
+/// until it knows enough of the committee to serve it.
+pub async fn readyz(Extension(gate): Extension<ReadinessGate>) -> StatusCode {
+    if gate.is_ready().await {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
 
 /// Publish handler which receives metrics from nodes.  Nodes will call us at this endpoint
 /// and we relay them to the upstream tsdb
 ///
-/// Clients will receive a response after successfully relaying the metrics upstream
+/// Clients will receive a response after successfully relaying the metrics upstream. While
+/// forwarding is paused (see `ForwardingGate::pause`), the push is buffered or dropped per the
+/// configured policy instead, and this returns `202 Accepted` without ever forwarding it.
 pub async fn publish_metrics(
     Extension(network): Extension<String>,
     Extension(client): Extension<ReqwestClient>,
+    Extension(forwarding): Extension<ForwardingGate>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(peer): Extension<SuiPeer>,
     LenDelimProtobuf(data): LenDelimProtobuf,
 ) -> (StatusCode, &'static str) {
-    convert_to_remote_write(
-        client.clone(),
-        NodeMetric {
-            host: peer.name,
-            network,
-            data,
-            peer_addr: Multiaddr::from(addr.ip()),
-            public_key: peer.public_key,
-        },
-    )
-    .await
+    let node_metric = NodeMetric {
+        host: peer.name,
+        network,
+        data,
+        peer_addr: Multiaddr::from(addr.ip()),
+        public_key: peer.public_key,
+    };
+    match forwarding.admit(node_metric) {
+        Some(node_metric) => convert_to_remote_write(client.clone(), node_metric).await,
+        None => (StatusCode::ACCEPTED, "forwarding paused"),
+    }
+}
+
+/// Returns the proxy's last successfully polled `SuiSystemStateSummary`, so downstream consumers
+/// that want committee data can reuse what the proxy already fetched instead of hitting the full
+/// node independently. 503 until the first poll completes.
+pub async fn get_system_state(
+    Extension(allower): Extension<Arc<SuiNodeProvider>>,
+) -> Result<Json<CachedSystemState>, StatusCode> {
+    allower
+        .cached_system_state()
+        .map(Json)
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)
+}
+
+/// Runs a one-shot poll against the configured RPC endpoint and reports what it found — latency,
+/// committee size, extracted count, per-skip-reason counts, and the diff it would produce against
+/// the current allow list — without installing anything (see
+/// `SuiNodeProvider::run_poll_diagnostic`). Gated behind the same peer auth as the proxy's other
+/// admin-facing routes, since it reveals the allow list's makeup to whoever can call it.
+pub async fn poll_diagnostic(
+    Extension(allower): Extension<Arc<SuiNodeProvider>>,
+) -> Result<Json<PollDiagnostic>, (StatusCode, String)> {
+    allower
+        .run_poll_diagnostic()
+        .await
+        .map(Json)
+        .map_err(|error| (StatusCode::BAD_GATEWAY, error.to_string()))
+}
+
+/// Streams allow-list changes (see `SuiNodeProvider::subscribe_peer_changes`) to the client as
+/// Server-Sent Events, one JSON-encoded `PeerChangeEvent` per event. A subscriber that falls far
+/// enough behind for `tokio::sync::broadcast` to drop events for it (`RecvError::Lagged`) is cut
+/// off rather than resynced, so a client never observes a silent gap in the stream; it's expected
+/// to reconnect, picking up from whatever the allow list looks like at that point.
+pub async fn stream_peer_changes(
+    Extension(allower): Extension<Arc<SuiNodeProvider>>,
+) -> Sse<impl futures::Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(allower.subscribe_peer_changes())
+        .take_while(|result| futures::future::ready(result.is_ok()))
+        .filter_map(|result| async move {
+            let record = result.ok()?;
+            let event = PeerChangeEvent::from(&record);
+            Some(Ok(Event::default().json_data(event).unwrap()))
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::{generate_self_cert, CertKeyPair};
+    use std::time::Duration;
+    use sui_types::sui_system_state::sui_system_state_summary::{
+        SuiSystemStateSummary, SuiValidatorSummary,
+    };
+
+    /// spawns a one-shot HTTP server on localhost that always responds with `body`, returning its url
+    async fn spawn_canned_http_server(body: String) -> String {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Response, Server};
+
+        let make_svc = make_service_fn(move |_| {
+            let body = body.clone();
+            async move {
+                Ok::<_, std::convert::Infallible>(service_fn(move |_req| {
+                    let body = body.clone();
+                    async move { Ok::<_, std::convert::Infallible>(Response::new(Body::from(body))) }
+                }))
+            }
+        });
+
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_svc);
+        let addr = server.local_addr();
+        tokio::spawn(async move {
+            let _ = server.await;
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn get_system_state_returns_503_before_the_first_poll_completes() {
+        let provider = Arc::new(SuiNodeProvider::new("".into(), Duration::from_secs(30)));
+
+        let result = get_system_state(Extension(provider)).await;
+
+        assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn get_system_state_reflects_the_most_recently_polled_summary() {
+        let summary = SuiSystemStateSummary {
+            epoch: 7,
+            active_validators: vec![SuiValidatorSummary {
+                name: "node-a".into(),
+                p2p_address: "/ip4/127.0.0.1/tcp/10000".into(),
+                primary_address: "empty".into(),
+                worker_address: "empty".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        #[derive(serde::Serialize)]
+        struct ResponseBody {
+            result: SuiSystemStateSummary,
+        }
+        let body = serde_json::to_string(&ResponseBody { result: summary }).unwrap();
+        let url = spawn_canned_http_server(body).await;
+
+        let provider = SuiNodeProvider::new(url, Duration::from_millis(10));
+        provider.poll_peer_list();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let result = get_system_state(Extension(Arc::new(provider)))
+            .await
+            .expect("expected a cached summary after a successful poll");
+
+        assert_eq!(result.epoch, 7);
+        assert_eq!(result.summary.active_validators.len(), 1);
+        assert_eq!(result.summary.active_validators[0].name, "node-a");
+    }
+
+    /// driving the same stream the SSE handler wraps (`BroadcastStream` over
+    /// `subscribe_peer_changes`) should yield an `added` event for a newly polled validator.
+    #[tokio::test]
+    async fn stream_peer_changes_emits_an_event_for_a_newly_polled_validator() {
+        let summary = SuiSystemStateSummary {
+            epoch: 1,
+            active_validators: vec![SuiValidatorSummary {
+                name: "node-a".into(),
+                p2p_address: "/ip4/127.0.0.1/tcp/10000".into(),
+                primary_address: "empty".into(),
+                worker_address: "empty".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        #[derive(serde::Serialize)]
+        struct ResponseBody {
+            result: SuiSystemStateSummary,
+        }
+        let body = serde_json::to_string(&ResponseBody { result: summary }).unwrap();
+        let url = spawn_canned_http_server(body).await;
+
+        let provider = SuiNodeProvider::new(url, Duration::from_millis(10));
+        let mut stream = BroadcastStream::new(provider.subscribe_peer_changes());
+
+        provider.poll_peer_list();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("expected a peer-change event within the timeout")
+            .expect("stream ended unexpectedly")
+            .expect("event should not be a lagged error");
+        let parsed = crate::peers::PeerChangeEvent::from(&event);
+        assert_eq!(parsed.kind, "added");
+        assert_eq!(parsed.name, "node-a");
+    }
+
+    #[tokio::test]
+    async fn poll_diagnostic_reports_the_expected_json_structure() {
+        let CertKeyPair(_, admitted_key) = generate_self_cert("admitted".into());
+        let CertKeyPair(_, dropped_key) = generate_self_cert("dropped".into());
+        let summary = SuiSystemStateSummary {
+            epoch: 3,
+            active_validators: vec![
+                SuiValidatorSummary {
+                    name: "node-a".into(),
+                    network_pubkey_bytes: Vec::from(admitted_key.as_bytes()),
+                    p2p_address: "/ip4/127.0.0.1/tcp/10000".into(),
+                    primary_address: "empty".into(),
+                    worker_address: "empty".into(),
+                    ..Default::default()
+                },
+                SuiValidatorSummary {
+                    name: "node-b".into(),
+                    network_pubkey_bytes: Vec::from(dropped_key.as_bytes()),
+                    p2p_address: "".into(),
+                    primary_address: "empty".into(),
+                    worker_address: "empty".into(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        #[derive(serde::Serialize)]
+        struct ResponseBody {
+            result: SuiSystemStateSummary,
+        }
+        let body = serde_json::to_string(&ResponseBody { result: summary }).unwrap();
+        let url = spawn_canned_http_server(body).await;
+
+        let provider = Arc::new(SuiNodeProvider::new(url.clone(), Duration::from_secs(30)));
+
+        let report = poll_diagnostic(Extension(provider))
+            .await
+            .expect("expected a successful diagnostic against a well-formed canned response");
+        let report = report.0;
+
+        assert_eq!(report.rpc_url, url);
+        assert_eq!(report.committee_size, 2);
+        assert_eq!(report.extracted_count, 1);
+        assert_eq!(report.skip_reasons.get("unparsable_p2p_address"), Some(&1));
+        assert_eq!(report.would_remove.len(), 0);
+        assert_eq!(report.would_add.len(), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        for field in [
+            "rpc_url",
+            "latency_ms",
+            "committee_size",
+            "extracted_count",
+            "skip_reasons",
+            "would_remove",
+            "would_add",
+        ] {
+            assert!(json.get(field).is_some(), "expected field {field} in the report JSON");
+        }
+    }
 }
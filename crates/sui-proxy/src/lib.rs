@@ -8,6 +8,7 @@ pub mod metrics;
 pub mod middleware;
 pub mod peers;
 pub mod prom_to_mimir;
+pub mod relabel;
 pub mod remote_write;
 
 /// var extracts environment variables at runtime with a default fallback value
@@ -105,7 +106,14 @@ mod tests {
         async fn handler(tls_info: axum::Extension<TlsConnectionInfo>) -> String {
             tls_info.public_key().unwrap().to_string()
         }
-        let app = admin::app("unittest-network".into(), client, Some(allower.clone()));
+        let forwarding = consumer::ForwardingGate::new(consumer::ForwardingPausePolicy::Drop);
+        let app = admin::app(
+            "unittest-network".into(),
+            client,
+            Some(allower.clone()),
+            forwarding,
+            None,
+        );
 
         let listener = std::net::TcpListener::bind("localhost:0").unwrap();
         let server_address = listener.local_addr().unwrap();
@@ -135,8 +143,18 @@ mod tests {
             client_pub_key.to_owned(),
             peers::SuiPeer {
                 name: "some-node".into(),
+                raw_name: "some-node".into(),
                 p2p_address: Multiaddr::empty(),
+                p2p_addresses: vec![Multiaddr::empty()],
                 public_key: client_pub_key.to_owned(),
+                voting_power: 0,
+                pending_removal: false,
+                pending_governance: false,
+                no_dial: false,
+                additional_keys: Vec::new(),
+                sui_address: "0x0".into(),
+                geo: None,
+                registry_metadata: None,
             },
         );
 
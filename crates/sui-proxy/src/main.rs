@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use fastcrypto::traits::ToFromBytes;
 use sui_proxy::config::ProxyConfig;
 use sui_proxy::{
     admin::{
@@ -10,7 +11,9 @@ use sui_proxy::{
         make_reqwest_client, server,
     },
     config::load,
+    consumer::{ForwardingGate, ForwardingPausePolicy},
     metrics,
+    peers::{peers_diff_report, MetricNamingConfig, PeersDiffConfig},
 };
 use sui_tls::TlsAcceptor;
 use telemetry_subscribers::TelemetryConfig;
@@ -28,6 +31,22 @@ struct Args {
         help = "Specify the config file path to use"
     )]
     config: String,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Poll the chain once and print the diff between the live committee and a roster file,
+    /// for operators verifying their expected set without standing up a full proxy.
+    PeersDiff {
+        /// the full node json-rpc url to poll
+        #[clap(long)]
+        rpc_url: String,
+        /// path to a roster file, one hex-encoded ed25519 network public key per line
+        #[clap(long)]
+        roster: std::path::PathBuf,
+    },
 }
 
 #[tokio::main]
@@ -36,6 +55,20 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
 
+    if let Some(Command::PeersDiff { rpc_url, roster }) = args.command {
+        let report = peers_diff_report(PeersDiffConfig { rpc_url, roster_path: roster }).await?;
+        println!("epoch: {}", report.epoch);
+        println!("added (on chain, not in roster):");
+        for key in &report.added {
+            println!("  {}", hex::encode(key.as_bytes()));
+        }
+        println!("removed (in roster, not on chain):");
+        for key in &report.removed {
+            println!("  {}", hex::encode(key.as_bytes()));
+        }
+        return Ok(());
+    }
+
     let config: ProxyConfig = load(args.config)?;
 
     info!(
@@ -45,7 +78,13 @@ async fn main() -> Result<()> {
 
     let listener = std::net::TcpListener::bind(config.listen_address).unwrap();
 
-    let (tls_config, allower) =
+    let metric_naming = MetricNamingConfig {
+        prefix: config.json_rpc.metric_prefix.clone(),
+        const_labels: config.json_rpc.metric_const_labels.clone(),
+    };
+    let readiness_min_peers = config.json_rpc.readiness_min_peers;
+
+    let (tls_config, mut allower) =
         if config.json_rpc.certificate_file.is_none() || config.json_rpc.private_key.is_none() {
             (
                 create_server_cert_default_allow(config.json_rpc.hostname.unwrap())
@@ -58,7 +97,6 @@ async fn main() -> Result<()> {
         };
     let acceptor = TlsAcceptor::new(tls_config);
     let client = make_reqwest_client(config.remote_write);
-    let app = app(config.network, client, allower);
 
     let registry_service = metrics::start_prometheus_server(config.metrics_address);
     let prometheus_registry = registry_service.default_registry();
@@ -66,6 +104,16 @@ async fn main() -> Result<()> {
         .register(mysten_metrics::uptime_metric(VERSION))
         .unwrap();
 
+    if let Some(allower) = &mut allower {
+        allower.set_metrics_with_naming(&prometheus_registry, metric_naming);
+        allower.poll_peer_list();
+    }
+
+    let mut forwarding = ForwardingGate::new(ForwardingPausePolicy::Drop);
+    forwarding.set_metrics(&prometheus_registry);
+
+    let app = app(config.network, client, allower, forwarding, readiness_min_peers);
+
     server(listener, app, Some(acceptor)).await.unwrap();
 
     Ok(())
@@ -6,9 +6,9 @@ use axum::{
     body::Bytes,
     extract::{Extension, FromRequest},
     headers::ContentType,
-    http::{Request, StatusCode},
+    http::{header, HeaderValue, Request, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
     BoxError, TypedHeader,
 };
 use bytes::Buf;
@@ -40,11 +40,27 @@ pub async fn expect_valid_public_key<B>(
     mut request: Request<B>,
     next: Next<B>,
 ) -> Result<Response, (StatusCode, &'static str)> {
-    let Some(peer) = allower.get(tls_connect_info.public_key().unwrap()) else {
+    let Some(public_key) = tls_connect_info.public_key() else {
+        error!("client connected without presenting a certificate on a route that requires one");
+        return Err((StatusCode::UNAUTHORIZED, "a client certificate is required"));
+    };
+
+    let Some(peer) = allower.get(public_key) else {
         error!("node with unknown pub key tried to connect");
         return Err((StatusCode::FORBIDDEN, "unknown clients are not allowed"));
     };
 
+    if allower.session_expired(tls_connect_info.established_at()) {
+        error!("peer {} exceeded the configured max session age; closing the connection to force a re-handshake", peer.name);
+        // force the TLS session closed rather than merely rejecting this request, so the client
+        // can't keep riding the same aged handshake on its next request over a reused connection
+        let mut response = StatusCode::UNAUTHORIZED.into_response();
+        response
+            .headers_mut()
+            .insert(header::CONNECTION, HeaderValue::from_static("close"));
+        return Ok(response);
+    }
+
     request.extensions_mut().insert(peer);
     Ok(next.run(request).await)
 }
@@ -0,0 +1,289 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::{PeerChangeKind, PeerChangeRecord};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
+
+/// AuditFileConfig configures a background task that durably appends every recorded
+/// `PeerChangeRecord` to a structured JSONL file at `path`, for compliance review beyond what the
+/// in-memory audit log (bounded, lost on restart) retains. See `SuiNodeProvider::set_audit_file`.
+#[derive(Clone, Debug)]
+pub struct AuditFileConfig {
+    pub path: PathBuf,
+    /// once the file reaches this size, it's rotated to `<path>.<unix timestamp>` and a fresh
+    /// file started. `None` disables size-based rotation.
+    pub max_size_bytes: Option<u64>,
+    /// once the file has been open longer than this (since the sink opened it or last rotated
+    /// it), it's rotated the same way size-based rotation does. `None` disables age-based
+    /// rotation.
+    pub max_age: Option<std::time::Duration>,
+}
+
+/// one line of the audit file; field names are chosen to read naturally in a JSONL viewer rather
+/// than mirroring `PeerChangeRecord`/`PeerChangeEvent`'s internal naming.
+#[derive(Debug, serde::Serialize)]
+struct AuditFileEntry {
+    /// seconds since the Unix epoch
+    timestamp: u64,
+    epoch: u64,
+    name: String,
+    sui_address: String,
+    public_key: String,
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_public_key: Option<String>,
+}
+
+impl From<&PeerChangeRecord> for AuditFileEntry {
+    fn from(record: &PeerChangeRecord) -> Self {
+        let (kind, previous_public_key) = match &record.kind {
+            PeerChangeKind::Added => ("added".to_owned(), None),
+            PeerChangeKind::Removed => ("removed".to_owned(), None),
+            PeerChangeKind::KeyRotated { old_key, .. } => (
+                "key_rotated".to_owned(),
+                Some(hex::encode(old_key.as_bytes())),
+            ),
+        };
+        AuditFileEntry {
+            timestamp: record
+                .timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|age| age.as_secs())
+                .unwrap_or(0),
+            epoch: record.epoch,
+            name: record.name.to_owned(),
+            sui_address: record.sui_address.to_owned(),
+            public_key: hex::encode(record.public_key.as_bytes()),
+            kind,
+            previous_public_key,
+        }
+    }
+}
+
+/// AuditFileWriter owns the open handle to `config.path` and the bookkeeping needed to decide
+/// when to rotate it, across calls to `append`.
+struct AuditFileWriter {
+    config: AuditFileConfig,
+    file: std::fs::File,
+    size_bytes: u64,
+    opened_at: SystemTime,
+}
+
+impl AuditFileWriter {
+    fn open(config: AuditFileConfig) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+        let size_bytes = file.metadata()?.len();
+        Ok(Self {
+            config,
+            file,
+            size_bytes,
+            opened_at: SystemTime::now(),
+        })
+    }
+
+    fn should_rotate(&self) -> bool {
+        if let Some(max_size_bytes) = self.config.max_size_bytes {
+            if self.size_bytes >= max_size_bytes {
+                return true;
+            }
+        }
+        if let Some(max_age) = self.config.max_age {
+            if self.opened_at.elapsed().unwrap_or_default() >= max_age {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|age| age.as_secs())
+            .unwrap_or(0);
+        let rotated_path = rotated_path(&self.config.path, timestamp);
+        std::fs::rename(&self.config.path, &rotated_path)?;
+        *self = Self::open(self.config.clone())?;
+        Ok(())
+    }
+
+    fn append(&mut self, entry: &AuditFileEntry) -> std::io::Result<()> {
+        if self.should_rotate() {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.size_bytes += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// rotated_path derives the path a rotated audit file is moved to, appending `.<timestamp>` to
+/// the original file name (plus a `.<n>` counter if that path is already taken by an earlier
+/// rotation within the same second) so repeated rotations of the same `path` don't collide.
+fn rotated_path(path: &Path, timestamp: u64) -> PathBuf {
+    let mut base = path.as_os_str().to_owned();
+    base.push(format!(".{timestamp}"));
+    let mut rotated = PathBuf::from(&base);
+    let mut counter = 1u32;
+    while rotated.exists() {
+        let mut candidate = base.clone();
+        candidate.push(format!(".{counter}"));
+        rotated = PathBuf::from(candidate);
+        counter += 1;
+    }
+    rotated
+}
+
+/// run_audit_file_sink appends every `PeerChangeRecord` broadcast on `peer_change_rx` to
+/// `config.path` as a JSONL line, rotating the file per `config.max_size_bytes`/`config.max_age`.
+/// A write or rotation failure is logged and that event is dropped rather than the task exiting,
+/// so a transient disk issue doesn't silently stop every future append too. Exits once `shutdown`
+/// is cancelled or the broadcast channel closes (the provider it belongs to was dropped).
+pub(crate) async fn run_audit_file_sink(
+    mut peer_change_rx: broadcast::Receiver<PeerChangeRecord>,
+    config: AuditFileConfig,
+    shutdown: CancellationToken,
+) {
+    let mut writer = match AuditFileWriter::open(config.clone()) {
+        Ok(writer) => writer,
+        Err(error) => {
+            error!(
+                "unable to open audit file {:?}: {error}; the audit file sink will not run",
+                config.path
+            );
+            return;
+        }
+    };
+
+    loop {
+        let record = tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            result = peer_change_rx.recv() => result,
+        };
+        let record = match record {
+            Ok(record) => record,
+            Err(broadcast::error::RecvError::Closed) => break,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!(
+                    "audit file sink lagged behind the peer-change broadcast by {skipped} \
+                     events; continuing from the next one"
+                );
+                continue;
+            }
+        };
+
+        if let Err(error) = writer.append(&AuditFileEntry::from(&record)) {
+            error!("unable to append to audit file {:?}: {error}", writer.config.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::ed25519::Ed25519KeyPair;
+    use fastcrypto::traits::KeyPair;
+
+    fn sample_record(name: &str) -> PeerChangeRecord {
+        let public_key = Ed25519KeyPair::generate(&mut rand::thread_rng())
+            .public()
+            .to_owned();
+        PeerChangeRecord {
+            public_key,
+            name: name.to_owned(),
+            sui_address: "0xabc".into(),
+            epoch: 7,
+            kind: PeerChangeKind::Added,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    fn read_lines(path: &Path) -> Vec<String> {
+        std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn run_audit_file_sink_appends_one_jsonl_line_per_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let (peer_change_tx, peer_change_rx) = broadcast::channel(16);
+        let shutdown = CancellationToken::new();
+
+        let task = tokio::spawn(run_audit_file_sink(
+            peer_change_rx,
+            AuditFileConfig {
+                path: path.clone(),
+                max_size_bytes: None,
+                max_age: None,
+            },
+            shutdown.clone(),
+        ));
+
+        peer_change_tx.send(sample_record("node-a")).unwrap();
+        peer_change_tx.send(sample_record("node-b")).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        shutdown.cancel();
+        task.await.unwrap();
+
+        let lines = read_lines(&path);
+        assert_eq!(lines.len(), 2);
+        let first: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["name"], "node-a");
+        assert_eq!(first["epoch"], 7);
+        assert_eq!(first["sui_address"], "0xabc");
+        assert_eq!(first["kind"], "added");
+    }
+
+    #[tokio::test]
+    async fn run_audit_file_sink_rotates_once_the_configured_size_is_exceeded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let (peer_change_tx, peer_change_rx) = broadcast::channel(16);
+        let shutdown = CancellationToken::new();
+
+        // a single entry is comfortably under 200 bytes; this forces a rotation on every append
+        // after the first.
+        let task = tokio::spawn(run_audit_file_sink(
+            peer_change_rx,
+            AuditFileConfig {
+                path: path.clone(),
+                max_size_bytes: Some(1),
+                max_age: None,
+            },
+            shutdown.clone(),
+        ));
+
+        peer_change_tx.send(sample_record("node-a")).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+        peer_change_tx.send(sample_record("node-b")).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+        shutdown.cancel();
+        task.await.unwrap();
+
+        // the live file only holds whatever was written since the last rotation
+        let live_lines = read_lines(&path);
+        assert_eq!(live_lines.len(), 1, "expected the second event in the post-rotation file");
+
+        let rotated_files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("audit.jsonl."))
+            .collect();
+        assert_eq!(rotated_files.len(), 1, "expected exactly one rotated file");
+        let rotated_lines = read_lines(&rotated_files[0].path());
+        assert_eq!(rotated_lines.len(), 1, "expected the first event in the rotated file");
+    }
+}
@@ -0,0 +1,487 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::clock::is_fresh;
+use crate::peers::{PeerProviderError, SuiPeer};
+use fastcrypto::ed25519::{Ed25519KeyPair, Ed25519PublicKey, Ed25519Signature};
+use fastcrypto::traits::{Signer, ToFromBytes, VerifyingKey};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// the on-disk representation of a single allow-list entry, kept separate from `SuiPeer` so the
+/// cache format doesn't shift if `SuiPeer`'s in-memory shape changes
+#[derive(Serialize, Deserialize)]
+struct CachedPeer {
+    name: String,
+    raw_name: String,
+    p2p_address: String,
+    /// every p2p multiaddr the chain advertised, `p2p_address` among them. Defaults to empty on
+    /// a cache written before this field existed; `from_cached` falls back to `p2p_address` alone
+    /// in that case.
+    #[serde(default)]
+    p2p_addresses: Vec<String>,
+    public_key: String,
+    #[serde(default)]
+    voting_power: u64,
+    #[serde(default)]
+    pending_removal: bool,
+    #[serde(default)]
+    no_dial: bool,
+    /// the validator's `sui_address`. Defaults to empty on a cache written before this field
+    /// existed; `from_cached` falls back to that empty string rather than refusing to load.
+    #[serde(default)]
+    sui_address: String,
+}
+
+/// the signed envelope written to disk: the serialized peer list plus a hex-encoded ed25519
+/// signature over it, so a tampered cache file fails verification on load rather than silently
+/// injecting rogue peers into the allow list
+#[derive(Serialize, Deserialize)]
+struct SignedCache {
+    peers: Vec<CachedPeer>,
+    /// seconds since the Unix epoch when this cache was written, used by `load_verified_cache`
+    /// and `fetch_verified_snapshot` to reject a stale cache. Defaults to 0 ("unknown age") for a
+    /// cache written before this field existed, which is always treated as fresh since we have no
+    /// basis to say otherwise.
+    #[serde(default)]
+    written_at: u64,
+    signature: String,
+}
+
+/// UNKNOWN_WRITTEN_AT marks a `SignedCache` written before staleness tracking existed.
+const UNKNOWN_WRITTEN_AT: u64 = 0;
+
+fn to_cached(peer: &SuiPeer) -> CachedPeer {
+    CachedPeer {
+        name: peer.name.clone(),
+        raw_name: peer.raw_name.clone(),
+        p2p_address: peer.p2p_address.to_string(),
+        p2p_addresses: peer.p2p_addresses.iter().map(|addr| addr.to_string()).collect(),
+        public_key: hex::encode(peer.public_key.as_bytes()),
+        voting_power: peer.voting_power,
+        pending_removal: peer.pending_removal,
+        no_dial: peer.no_dial,
+        sui_address: peer.sui_address.clone(),
+        // not persisted: `additional_keys` isn't derived from chain data today (see
+        // `SuiPeer::additional_keys`), so there's nothing for a real cache write to carry, and
+        // `from_cached` always restores an empty vec.
+    }
+}
+
+fn from_cached(cached: &CachedPeer) -> Result<(Ed25519PublicKey, SuiPeer), PeerProviderError> {
+    let public_key_bytes =
+        hex::decode(&cached.public_key).map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    let public_key = Ed25519PublicKey::from_bytes(&public_key_bytes)
+        .map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    let p2p_address = multiaddr::Multiaddr::from_str(&cached.p2p_address)
+        .map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    let p2p_addresses = if cached.p2p_addresses.is_empty() {
+        vec![p2p_address.clone()]
+    } else {
+        cached
+            .p2p_addresses
+            .iter()
+            .map(|raw| multiaddr::Multiaddr::from_str(raw))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| PeerProviderError::Cache(error.to_string()))?
+    };
+    Ok((
+        public_key.clone(),
+        SuiPeer {
+            name: cached.name.clone(),
+            raw_name: cached.raw_name.clone(),
+            p2p_address,
+            p2p_addresses,
+            public_key,
+            voting_power: cached.voting_power,
+            pending_removal: cached.pending_removal,
+            no_dial: cached.no_dial,
+            additional_keys: Vec::new(),
+            sui_address: cached.sui_address.clone(),
+            // not persisted: governance-pending status is supplied out of band on every run (see
+            // `SuiPeer::pending_governance`), geoip enrichment is re-derived from whatever
+            // database is currently loaded (see `SuiPeer::geo`), and registry metadata is
+            // re-derived from whatever metadata registry is currently loaded (see
+            // `SuiPeer::registry_metadata`), so none of the three carries over from a stale cache.
+            pending_governance: false,
+            geo: None,
+            registry_metadata: None,
+        },
+    ))
+}
+
+/// SignedSnapshot is the same signed envelope `write_signed_cache` persists to disk, produced in
+/// memory instead: for a caller that wants to serve or forward it directly (e.g. to another proxy
+/// warm-starting from `warm_from_snapshot_url`) without going through a file. Verify with
+/// `verify_snapshot` the same way a fetched or loaded cache is verified.
+pub struct SignedSnapshot {
+    pub bytes: Vec<u8>,
+}
+
+/// signed_snapshot serializes `peers` and signs the serialized bytes with `keypair`, the same
+/// envelope `write_signed_cache` writes to disk, returned in memory instead. `written_at` is
+/// stamped into the envelope so `verify_snapshot` can evaluate its age.
+pub fn signed_snapshot(
+    peers: &IndexMap<Ed25519PublicKey, SuiPeer>,
+    keypair: &Ed25519KeyPair,
+    written_at: SystemTime,
+) -> Result<SignedSnapshot, PeerProviderError> {
+    let cached: Vec<CachedPeer> = peers.values().map(to_cached).collect();
+    let payload =
+        serde_json::to_vec(&cached).map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    let signature = keypair.sign(&payload);
+    let envelope = SignedCache {
+        peers: cached,
+        written_at: written_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|age| age.as_secs())
+            .unwrap_or(UNKNOWN_WRITTEN_AT),
+        signature: hex::encode(signature.as_bytes()),
+    };
+    let bytes =
+        serde_json::to_vec(&envelope).map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    Ok(SignedSnapshot { bytes })
+}
+
+/// verify_snapshot is the consuming-side counterpart to `signed_snapshot`: verifies `snapshot`
+/// against `public_key` and returns its peers only if the signature verifies and, when `max_age`
+/// is set, it isn't stale, the same rules `decode_and_verify` applies to a loaded or fetched
+/// cache. For a caller that already has the signed bytes in hand rather than needing to read them
+/// from a file or url.
+pub fn verify_snapshot(
+    snapshot: &SignedSnapshot,
+    public_key: &Ed25519PublicKey,
+    now: SystemTime,
+    max_age: Option<Duration>,
+    max_clock_skew: Duration,
+) -> Result<IndexMap<Ed25519PublicKey, SuiPeer>, PeerProviderError> {
+    decode_and_verify(&snapshot.bytes, public_key, now, max_age, max_clock_skew)
+}
+
+/// write_signed_cache serializes `peers` and signs the serialized bytes with `keypair`, writing
+/// the envelope to `path` for `load_verified_cache` to pick up on a subsequent restart.
+/// `written_at` is stamped into the envelope so a later load can evaluate its age.
+pub fn write_signed_cache(
+    path: &Path,
+    peers: &IndexMap<Ed25519PublicKey, SuiPeer>,
+    keypair: &Ed25519KeyPair,
+    written_at: SystemTime,
+) -> Result<(), PeerProviderError> {
+    let snapshot = signed_snapshot(peers, keypair, written_at)?;
+    std::fs::write(path, snapshot.bytes).map_err(|error| PeerProviderError::Cache(error.to_string()))
+}
+
+/// load_verified_cache reads the envelope at `path` and returns its peers only if the signature
+/// verifies against `public_key` and, when `max_age` is set, the cache isn't older than `max_age`
+/// relative to `now` (tolerating a backward clock jump of up to `max_clock_skew`, see `is_fresh`).
+/// A missing file, a decode failure, a failed verification, or a stale cache are all treated the
+/// same way by callers: fall back to live polling rather than trust a cache that might be tampered
+/// with or outdated.
+pub fn load_verified_cache(
+    path: &Path,
+    public_key: &Ed25519PublicKey,
+    now: SystemTime,
+    max_age: Option<Duration>,
+    max_clock_skew: Duration,
+) -> Result<IndexMap<Ed25519PublicKey, SuiPeer>, PeerProviderError> {
+    let serialized =
+        std::fs::read(path).map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    decode_and_verify(&serialized, public_key, now, max_age, max_clock_skew)
+}
+
+/// fetch_verified_snapshot fetches a signed allow-list snapshot from `url` (produced by another
+/// proxy's `write_signed_cache`) and returns its peers only if the signature verifies against
+/// `public_key` and it isn't stale, per the same `max_age`/`max_clock_skew` rules as
+/// `load_verified_cache`. Intended to be called once at startup, before live polling begins, to
+/// shorten the window where the allow list is empty on a cold start.
+pub async fn fetch_verified_snapshot(
+    url: &str,
+    public_key: &Ed25519PublicKey,
+    now: SystemTime,
+    max_age: Option<Duration>,
+    max_clock_skew: Duration,
+) -> Result<IndexMap<Ed25519PublicKey, SuiPeer>, PeerProviderError> {
+    let response = reqwest::get(url)
+        .await
+        .map_err(|error| PeerProviderError::Network(error.to_string()))?;
+    let body = response
+        .bytes()
+        .await
+        .map_err(|error| PeerProviderError::Network(error.to_string()))?;
+    decode_and_verify(&body, public_key, now, max_age, max_clock_skew)
+}
+
+/// decode_and_verify is the decode/verify path shared by the disk cache and the warm-cache
+/// snapshot URL: both are the same signed envelope, just sourced differently.
+fn decode_and_verify(
+    serialized: &[u8],
+    public_key: &Ed25519PublicKey,
+    now: SystemTime,
+    max_age: Option<Duration>,
+    max_clock_skew: Duration,
+) -> Result<IndexMap<Ed25519PublicKey, SuiPeer>, PeerProviderError> {
+    let envelope: SignedCache = serde_json::from_slice(serialized)
+        .map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+
+    if let Some(max_age) = max_age {
+        if envelope.written_at != UNKNOWN_WRITTEN_AT {
+            let written_at = SystemTime::UNIX_EPOCH + Duration::from_secs(envelope.written_at);
+            if !is_fresh(written_at, now, max_age, max_clock_skew) {
+                return Err(PeerProviderError::Cache(
+                    "cache is older than the configured max age".into(),
+                ));
+            }
+        }
+    }
+
+    let payload = serde_json::to_vec(&envelope.peers)
+        .map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    let signature_bytes =
+        hex::decode(&envelope.signature).map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    let signature = Ed25519Signature::from_bytes(&signature_bytes)
+        .map_err(|error| PeerProviderError::Cache(error.to_string()))?;
+    public_key
+        .verify(&payload, &signature)
+        .map_err(|_| PeerProviderError::Cache("cache signature verification failed".into()))?;
+
+    envelope.peers.iter().map(from_cached).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::traits::KeyPair;
+
+    fn sample_peers(keypair: &Ed25519KeyPair) -> IndexMap<Ed25519PublicKey, SuiPeer> {
+        let public_key = keypair.public().to_owned();
+        let mut peers = IndexMap::new();
+        peers.insert(
+            public_key.clone(),
+            SuiPeer {
+                name: "node-a".into(),
+                raw_name: "node-a".into(),
+                p2p_address: "/ip4/127.0.0.1/tcp/8084".parse().unwrap(),
+                p2p_addresses: vec!["/ip4/127.0.0.1/tcp/8084".parse().unwrap()],
+                public_key,
+                voting_power: 42,
+                pending_removal: false,
+                no_dial: false,
+                additional_keys: Vec::new(),
+                sui_address: "0xabc123".into(),
+                pending_governance: false,
+                geo: None,
+                registry_metadata: None,
+            },
+        );
+        peers
+    }
+
+    #[test]
+    fn load_verified_cache_round_trips_a_validly_signed_cache() {
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let dir = tempfile_dir();
+        let path = dir.join("allow-list-cache.json");
+        write_signed_cache(&path, &peers, &keypair, SystemTime::now()).unwrap();
+
+        let loaded =
+            load_verified_cache(&path, &keypair.public().to_owned(), SystemTime::now(), None, Duration::ZERO)
+                .unwrap();
+        assert_eq!(loaded, peers);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn load_verified_cache_rejects_a_tampered_cache() {
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let dir = tempfile_dir();
+        let path = dir.join("allow-list-cache.json");
+        write_signed_cache(&path, &peers, &keypair, SystemTime::now()).unwrap();
+
+        let mut serialized = std::fs::read(&path).unwrap();
+        // flip a byte in the middle of the file, landing inside the serialized peer list
+        let mid = serialized.len() / 2;
+        serialized[mid] ^= 0xff;
+        std::fs::write(&path, serialized).unwrap();
+
+        let error = load_verified_cache(&path, &keypair.public().to_owned(), SystemTime::now(), None, Duration::ZERO)
+            .unwrap_err();
+        assert!(matches!(error, PeerProviderError::Cache(_)));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn verify_snapshot_round_trips_a_validly_signed_snapshot() {
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let snapshot = signed_snapshot(&peers, &keypair, SystemTime::now()).unwrap();
+        let verified = verify_snapshot(
+            &snapshot,
+            &keypair.public().to_owned(),
+            SystemTime::now(),
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert_eq!(verified, peers);
+    }
+
+    #[test]
+    fn verify_snapshot_rejects_a_tampered_snapshot() {
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let mut snapshot = signed_snapshot(&peers, &keypair, SystemTime::now()).unwrap();
+        // flip a byte in the middle of the payload, landing inside the serialized peer list
+        let mid = snapshot.bytes.len() / 2;
+        snapshot.bytes[mid] ^= 0xff;
+
+        let error = verify_snapshot(
+            &snapshot,
+            &keypair.public().to_owned(),
+            SystemTime::now(),
+            None,
+            Duration::ZERO,
+        )
+        .unwrap_err();
+        assert!(matches!(error, PeerProviderError::Cache(_)));
+    }
+
+    #[test]
+    fn load_verified_cache_rejects_a_cache_older_than_max_age() {
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let dir = tempfile_dir();
+        let path = dir.join("allow-list-cache.json");
+        let written_at = SystemTime::now() - Duration::from_secs(120);
+        write_signed_cache(&path, &peers, &keypair, written_at).unwrap();
+
+        let error = load_verified_cache(
+            &path,
+            &keypair.public().to_owned(),
+            SystemTime::now(),
+            Some(Duration::from_secs(60)),
+            Duration::ZERO,
+        )
+        .unwrap_err();
+        assert!(matches!(error, PeerProviderError::Cache(_)));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn load_verified_cache_tolerates_a_backward_clock_jump_within_skew() {
+        // simulate the proxy's own clock having jumped backward (e.g. a VM migration) relative to
+        // when the cache was written: `now` reads as slightly earlier than `written_at`.
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let dir = tempfile_dir();
+        let path = dir.join("allow-list-cache.json");
+        let written_at = SystemTime::now();
+        write_signed_cache(&path, &peers, &keypair, written_at).unwrap();
+        let now = written_at - Duration::from_secs(2);
+
+        let loaded = load_verified_cache(
+            &path,
+            &keypair.public().to_owned(),
+            now,
+            Some(Duration::from_secs(60)),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(loaded, peers, "a small backward clock jump should still read as fresh");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// spawns a one-shot HTTP server on localhost that always responds with `body`, returning its url
+    async fn spawn_canned_http_server(body: Vec<u8>) -> String {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Response, Server};
+
+        let make_svc = make_service_fn(move |_| {
+            let body = body.clone();
+            async move {
+                Ok::<_, std::convert::Infallible>(service_fn(move |_req| {
+                    let body = body.clone();
+                    async move { Ok::<_, std::convert::Infallible>(Response::new(Body::from(body))) }
+                }))
+            }
+        });
+
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_svc);
+        let addr = server.local_addr();
+        tokio::spawn(async move {
+            let _ = server.await;
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn fetch_verified_snapshot_loads_peers_from_a_trusted_url() {
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let dir = tempfile_dir();
+        let path = dir.join("snapshot.json");
+        write_signed_cache(&path, &peers, &keypair, SystemTime::now()).unwrap();
+        let serialized = std::fs::read(&path).unwrap();
+
+        let url = spawn_canned_http_server(serialized).await;
+        let loaded = fetch_verified_snapshot(&url, &keypair.public().to_owned(), SystemTime::now(), None, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(loaded, peers);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn fetch_verified_snapshot_rejects_a_tampered_snapshot() {
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let peers = sample_peers(&keypair);
+
+        let dir = tempfile_dir();
+        let path = dir.join("snapshot.json");
+        write_signed_cache(&path, &peers, &keypair, SystemTime::now()).unwrap();
+        let mut serialized = std::fs::read(&path).unwrap();
+        let mid = serialized.len() / 2;
+        serialized[mid] ^= 0xff;
+
+        let url = spawn_canned_http_server(serialized).await;
+        let error = fetch_verified_snapshot(&url, &keypair.public().to_owned(), SystemTime::now(), None, Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, PeerProviderError::Cache(_)));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn tempfile_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "sui-proxy-cache-test-{}",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+}
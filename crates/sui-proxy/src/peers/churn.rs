@@ -0,0 +1,151 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::PeerChangeRecord;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// PeerChangeBatch is one window's worth of `PeerChangeRecord`s coalesced together by
+/// `run_churn_coalescer`, see `SuiNodeProvider::subscribe_coalesced_peer_changes`.
+#[derive(Debug, Clone)]
+pub struct PeerChangeBatch {
+    pub changes: Vec<PeerChangeRecord>,
+}
+
+/// run_churn_coalescer consumes the uncoalesced `PeerChangeRecord` stream on `raw_rx` (the same
+/// one `subscribe_peer_changes` hands out) and re-emits it on `coalesced_tx` as at most one
+/// `PeerChangeBatch` per `window`, so a downstream reconfiguration consumer sees one consolidated
+/// notification during a churn spike instead of being paged once per flapping peer. The
+/// underlying allow list and audit log are unaffected — both already updated per change before
+/// this task even sees it; only this derived notification stream throttles. Exits once `shutdown`
+/// is cancelled or the raw channel closes (the provider it belongs to was dropped).
+pub(crate) async fn run_churn_coalescer(
+    mut raw_rx: broadcast::Receiver<PeerChangeRecord>,
+    coalesced_tx: broadcast::Sender<PeerChangeBatch>,
+    window: Duration,
+    shutdown: CancellationToken,
+) {
+    'outer: loop {
+        let first = tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            result = raw_rx.recv() => result,
+        };
+        let first = match first {
+            Ok(record) => record,
+            Err(broadcast::error::RecvError::Closed) => break,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!(
+                    "churn coalescer lagged behind the peer-change broadcast by {skipped} events; \
+                     continuing from the next one"
+                );
+                continue;
+            }
+        };
+
+        let mut batch = vec![first];
+        let deadline = tokio::time::sleep(window);
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break 'outer,
+                _ = &mut deadline => break,
+                result = raw_rx.recv() => match result {
+                    Ok(record) => batch.push(record),
+                    Err(broadcast::error::RecvError::Closed) => break 'outer,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => warn!(
+                        "churn coalescer lagged behind the peer-change broadcast by {skipped} \
+                         events while batching; continuing with what was already collected"
+                    ),
+                },
+            }
+        }
+
+        // no live subscribers is expected (and fine) between polls; nothing to do with the error.
+        let _ = coalesced_tx.send(PeerChangeBatch { changes: batch });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peers::PeerChangeKind;
+    use fastcrypto::ed25519::Ed25519KeyPair;
+    use fastcrypto::traits::KeyPair;
+    use std::time::SystemTime;
+
+    fn sample_record(name: &str) -> PeerChangeRecord {
+        let public_key = Ed25519KeyPair::generate(&mut rand::thread_rng())
+            .public()
+            .to_owned();
+        PeerChangeRecord {
+            public_key,
+            name: name.to_owned(),
+            sui_address: "0x0".into(),
+            epoch: 0,
+            kind: PeerChangeKind::Added,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn run_churn_coalescer_coalesces_rapid_changes_into_one_batch() {
+        let (raw_tx, raw_rx) = broadcast::channel(64);
+        let (coalesced_tx, mut coalesced_rx) = broadcast::channel(64);
+        let shutdown = CancellationToken::new();
+
+        let task = tokio::spawn(run_churn_coalescer(
+            raw_rx,
+            coalesced_tx,
+            Duration::from_millis(50),
+            shutdown.clone(),
+        ));
+
+        for i in 0..5 {
+            raw_tx.send(sample_record(&format!("node-{i}"))).unwrap();
+        }
+
+        let batch = tokio::time::timeout(Duration::from_secs(1), coalesced_rx.recv())
+            .await
+            .expect("expected a coalesced batch within the timeout")
+            .unwrap();
+        assert_eq!(batch.changes.len(), 5, "expected every rapid change coalesced into one batch");
+
+        shutdown.cancel();
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn run_churn_coalescer_emits_a_separate_batch_once_the_window_elapses() {
+        let (raw_tx, raw_rx) = broadcast::channel(64);
+        let (coalesced_tx, mut coalesced_rx) = broadcast::channel(64);
+        let shutdown = CancellationToken::new();
+
+        let task = tokio::spawn(run_churn_coalescer(
+            raw_rx,
+            coalesced_tx,
+            Duration::from_millis(20),
+            shutdown.clone(),
+        ));
+
+        raw_tx.send(sample_record("node-a")).unwrap();
+        let first_batch = tokio::time::timeout(Duration::from_secs(1), coalesced_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first_batch.changes.len(), 1);
+
+        raw_tx.send(sample_record("node-b")).unwrap();
+        let second_batch = tokio::time::timeout(Duration::from_secs(1), coalesced_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second_batch.changes.len(), 1);
+        assert_eq!(second_batch.changes[0].name, "node-b");
+
+        shutdown.cancel();
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Clock abstracts over `SystemTime::now()` so time-dependent allow-list behavior (audit log
+/// timestamps, and any future grace-period/staleness checks) can be driven deterministically in
+/// tests via `MockClock`, instead of depending on wall-clock time and sleeps.
+pub trait Clock: std::fmt::Debug {
+    fn now(&self) -> SystemTime;
+}
+
+/// SharedClock is the type `SuiNodeProvider` threads through to anything that needs the current
+/// time, so a single clock (real or mock) can be shared across the provider and its poll loop.
+pub type SharedClock = Arc<dyn Clock + Send + Sync>;
+
+/// SystemClock is the default, production `Clock`: a thin wrapper over `SystemTime::now()`.
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// MockClock is a `Clock` whose current time is set explicitly, for tests that need to advance
+/// time deterministically rather than sleeping and hoping a real clock moves far enough.
+#[derive(Debug)]
+pub struct MockClock {
+    now: RwLock<SystemTime>,
+}
+
+impl MockClock {
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            now: RwLock::new(now),
+        }
+    }
+
+    /// advance moves the mock clock forward by `duration`.
+    pub fn advance(&self, duration: std::time::Duration) {
+        let mut now = self.now.write().unwrap();
+        *now += duration;
+    }
+
+    /// set pins the mock clock to an exact point in time.
+    pub fn set(&self, now: SystemTime) {
+        *self.now.write().unwrap() = now;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        *self.now.read().unwrap()
+    }
+}
+
+/// is_fresh reports whether `timestamp` is still within `max_age` of `now`. A plain
+/// `now.duration_since(timestamp)` would error (or, if mishandled, read as infinitely stale) the
+/// moment the system clock jumps backward relative to when `timestamp` was recorded, e.g. during a
+/// VM migration. `max_clock_skew` tolerates backward jumps up to that bound: a `timestamp` that's
+/// merely ahead of `now` by no more than `max_clock_skew` is still treated as fresh rather than
+/// invalidating a cache or poll result that's actually current.
+pub fn is_fresh(
+    timestamp: SystemTime,
+    now: SystemTime,
+    max_age: Duration,
+    max_clock_skew: Duration,
+) -> bool {
+    match now.duration_since(timestamp) {
+        Ok(age) => age <= max_age,
+        Err(error) => error.duration() <= max_clock_skew,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_advance_moves_time_forward_by_the_given_duration() {
+        let start = SystemTime::UNIX_EPOCH;
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(std::time::Duration::from_secs(30));
+        assert_eq!(clock.now(), start + std::time::Duration::from_secs(30));
+    }
+
+    #[test]
+    fn mock_clock_set_pins_an_exact_time() {
+        let clock = MockClock::new(SystemTime::UNIX_EPOCH);
+        let pinned = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+        clock.set(pinned);
+        assert_eq!(clock.now(), pinned);
+    }
+
+    #[test]
+    fn system_clock_reports_a_time_that_does_not_go_backwards() {
+        let clock = SystemClock;
+        let first = clock.now();
+        let second = clock.now();
+        assert!(second >= first);
+    }
+
+    #[test]
+    fn is_fresh_accepts_a_timestamp_within_max_age() {
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let now = timestamp + Duration::from_secs(30);
+        assert!(is_fresh(timestamp, now, Duration::from_secs(60), Duration::ZERO));
+    }
+
+    #[test]
+    fn is_fresh_rejects_a_timestamp_older_than_max_age() {
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let now = timestamp + Duration::from_secs(90);
+        assert!(!is_fresh(timestamp, now, Duration::from_secs(60), Duration::ZERO));
+    }
+
+    #[test]
+    fn is_fresh_tolerates_a_small_backward_clock_jump_within_skew() {
+        // `now` lands 2 seconds before `timestamp`, as if the clock jumped back during a VM
+        // migration. Within `max_clock_skew`, the timestamp should still read as fresh rather
+        // than erroring out or looking infinitely stale.
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let now = timestamp - Duration::from_secs(2);
+        assert!(is_fresh(timestamp, now, Duration::from_secs(60), Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn is_fresh_rejects_a_backward_clock_jump_beyond_skew_tolerance() {
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let now = timestamp - Duration::from_secs(10);
+        assert!(!is_fresh(timestamp, now, Duration::from_secs(60), Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::{multiaddr_to_socket_addr, PeerChangeKind, PeerChangeRecord, SuiPeers};
+use fastcrypto::ed25519::Ed25519PublicKey;
+use fastcrypto::traits::ToFromBytes;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
+
+/// ConsulConfig configures an optional sink that registers/deregisters each peer as a Consul
+/// service as the allow list changes, see `SuiNodeProvider::set_consul`.
+#[derive(Clone, Debug)]
+pub struct ConsulConfig {
+    /// base URL of the Consul HTTP API, e.g. `http://127.0.0.1:8500`
+    pub url: String,
+    /// the Consul service name every peer is registered under; individual peers are told apart
+    /// by `service_id`, not by this name
+    pub service_name: String,
+}
+
+/// service_id derives the Consul service ID for `public_key`, stable across validator renames
+/// since it's derived from the key rather than the chain-reported name.
+fn service_id(public_key: &Ed25519PublicKey) -> String {
+    format!("sui-{}", hex::encode(public_key.as_bytes()))
+}
+
+#[derive(Serialize)]
+struct ConsulServiceRegistration {
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Address")]
+    address: String,
+    #[serde(rename = "Port")]
+    port: u16,
+    #[serde(rename = "Tags")]
+    tags: Vec<String>,
+}
+
+/// run_consul_sink reconciles the Consul service catalog against every `PeerChangeRecord`
+/// broadcast on `peer_change_rx`: an `Added` peer (or the new side of a `KeyRotated`) is
+/// registered by PUTting to `/v1/agent/service/register`, a `Removed` peer (or the old side of a
+/// `KeyRotated`) is deregistered via `/v1/agent/service/deregister/:id`. Exits once `shutdown` is
+/// cancelled or the broadcast channel closes (the provider it belongs to was dropped).
+pub(crate) async fn run_consul_sink(
+    mut peer_change_rx: broadcast::Receiver<PeerChangeRecord>,
+    nodes: SuiPeers,
+    config: ConsulConfig,
+    client: reqwest::Client,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let record = tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            result = peer_change_rx.recv() => result,
+        };
+        let record = match record {
+            Ok(record) => record,
+            Err(broadcast::error::RecvError::Closed) => break,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!(
+                    "consul sink lagged behind the peer-change broadcast by {skipped} events; \
+                     continuing from the next one"
+                );
+                continue;
+            }
+        };
+
+        match &record.kind {
+            PeerChangeKind::Added => register(&client, &config, &nodes, &record.public_key).await,
+            PeerChangeKind::Removed => deregister(&client, &config, &record.public_key).await,
+            PeerChangeKind::KeyRotated { old_key, .. } => {
+                deregister(&client, &config, old_key).await;
+                register(&client, &config, &nodes, &record.public_key).await;
+            }
+        }
+    }
+}
+
+/// register looks `public_key` up in the live allow list and PUTs its current address/tags to
+/// Consul. Silently does nothing if the peer is already gone again by the time we get to it (e.g.
+/// immediately superseded by a later change), or if its p2p address has no dialable host:port.
+async fn register(
+    client: &reqwest::Client,
+    config: &ConsulConfig,
+    nodes: &SuiPeers,
+    public_key: &Ed25519PublicKey,
+) {
+    let Some(peer) = nodes.read().unwrap().get(public_key).cloned() else {
+        return;
+    };
+    let Ok(socket_addr) = multiaddr_to_socket_addr(&peer.p2p_address) else {
+        warn!(
+            "not registering {} with Consul: p2p_address {} has no dialable host:port",
+            peer.name, peer.p2p_address
+        );
+        return;
+    };
+
+    let registration = ConsulServiceRegistration {
+        id: service_id(&peer.public_key),
+        name: config.service_name.clone(),
+        address: socket_addr.ip().to_string(),
+        port: socket_addr.port(),
+        tags: vec![format!("sui_address={}", peer.sui_address)],
+    };
+
+    let url = format!("{}/v1/agent/service/register", config.url);
+    match client.put(&url).json(&registration).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!(
+            "Consul registration of {} returned status {}",
+            peer.name,
+            response.status()
+        ),
+        Err(error) => error!("unable to register {} with Consul: {error}", peer.name),
+    }
+}
+
+/// deregister removes `public_key`'s service from Consul's catalog, regardless of whether it's
+/// still present in the live allow list (it's expected not to be, by the time this is called).
+async fn deregister(client: &reqwest::Client, config: &ConsulConfig, public_key: &Ed25519PublicKey) {
+    let id = service_id(public_key);
+    let url = format!("{}/v1/agent/service/deregister/{id}", config.url);
+    match client.put(&url).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!("Consul deregistration of {id} returned status {}", response.status()),
+        Err(error) => error!("unable to deregister {id} from Consul: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peers::{AllowListState, SuiPeer};
+    use fastcrypto::ed25519::Ed25519KeyPair;
+    use fastcrypto::traits::KeyPair;
+    use std::sync::{Arc, RwLock};
+    use std::time::SystemTime;
+
+    fn sample_peer(public_key: Ed25519PublicKey) -> SuiPeer {
+        SuiPeer {
+            name: "node-a".into(),
+            raw_name: "node-a".into(),
+            p2p_address: "/ip4/127.0.0.1/tcp/10000".parse().unwrap(),
+            p2p_addresses: vec!["/ip4/127.0.0.1/tcp/10000".parse().unwrap()],
+            public_key,
+            voting_power: 1_000,
+            pending_removal: false,
+            no_dial: false,
+            additional_keys: vec![],
+            sui_address: "0xabc".into(),
+            pending_governance: false,
+            geo: None,
+            registry_metadata: None,
+        }
+    }
+
+    /// spawns a server recording every request's method, path and body, always responding 200 OK
+    async fn spawn_mock_consul() -> (String, Arc<RwLock<Vec<(String, String, String)>>>) {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Response, Server};
+
+        let requests: Arc<RwLock<Vec<(String, String, String)>>> = Arc::new(RwLock::new(Vec::new()));
+        let requests_clone = requests.clone();
+
+        let make_svc = make_service_fn(move |_| {
+            let requests = requests_clone.clone();
+            async move {
+                Ok::<_, std::convert::Infallible>(service_fn(move |req| {
+                    let requests = requests.clone();
+                    async move {
+                        let method = req.method().to_string();
+                        let path = req.uri().path().to_string();
+                        let body_bytes = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                        let body = String::from_utf8(body_bytes.to_vec()).unwrap();
+                        requests.write().unwrap().push((method, path, body));
+                        Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_svc);
+        let addr = server.local_addr();
+        tokio::spawn(async move {
+            let _ = server.await;
+        });
+        (format!("http://{addr}"), requests)
+    }
+
+    #[tokio::test]
+    async fn run_consul_sink_registers_an_added_peer_and_deregisters_a_removed_one() {
+        let (url, requests) = spawn_mock_consul().await;
+        let public_key = Ed25519KeyPair::generate(&mut rand::thread_rng())
+            .public()
+            .to_owned();
+        let nodes: SuiPeers = Arc::new(RwLock::new(AllowListState::default()));
+        nodes
+            .write()
+            .unwrap()
+            .insert(public_key.clone(), sample_peer(public_key.clone()));
+
+        let (peer_change_tx, peer_change_rx) = broadcast::channel(16);
+        let shutdown = CancellationToken::new();
+        let config = ConsulConfig {
+            url,
+            service_name: "sui-validator".into(),
+        };
+
+        let task = tokio::spawn(run_consul_sink(
+            peer_change_rx,
+            nodes.clone(),
+            config,
+            reqwest::Client::new(),
+            shutdown.clone(),
+        ));
+
+        peer_change_tx
+            .send(PeerChangeRecord {
+                public_key: public_key.clone(),
+                name: "node-a".into(),
+                sui_address: "0x0".into(),
+                epoch: 0,
+                kind: PeerChangeKind::Added,
+                timestamp: SystemTime::now(),
+            })
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        nodes.write().unwrap().remove(&public_key);
+        peer_change_tx
+            .send(PeerChangeRecord {
+                public_key: public_key.clone(),
+                name: "node-a".into(),
+                sui_address: "0x0".into(),
+                epoch: 0,
+                kind: PeerChangeKind::Removed,
+                timestamp: SystemTime::now(),
+            })
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        shutdown.cancel();
+        task.await.unwrap();
+
+        let requests = requests.read().unwrap();
+        assert_eq!(requests.len(), 2, "expected one register and one deregister call");
+
+        let id = service_id(&public_key);
+        let (method, path, body) = &requests[0];
+        assert_eq!(method, "PUT");
+        assert_eq!(path, "/v1/agent/service/register");
+        assert!(body.contains(&id));
+        assert!(body.contains("127.0.0.1"));
+
+        let (method, path, _body) = &requests[1];
+        assert_eq!(method, "PUT");
+        assert_eq!(path, format!("/v1/agent/service/deregister/{id}"));
+    }
+}
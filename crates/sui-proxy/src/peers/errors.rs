@@ -0,0 +1,35 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// PeerProviderError captures the ways a call to the upstream full node's json-rpc can fail,
+/// so that callers (e.g. backoff/metrics code) can match on the variant instead of string
+/// sniffing an `anyhow::Error`.
+#[derive(Debug, thiserror::Error)]
+pub enum PeerProviderError {
+    #[error("network error while contacting rpc: {0}")]
+    Network(String),
+    #[error("timed out while contacting rpc")]
+    Timeout,
+    #[error("unable to decode rpc response: {0}")]
+    Decode(String),
+    #[error("rpc returned an error: code {code}, message: {message}")]
+    RpcError { code: i64, message: String },
+    #[error("rpc returned an empty validator committee")]
+    EmptyCommittee,
+    #[error("allow-list cache error: {0}")]
+    Cache(String),
+    #[error("rpc call cancelled before it completed")]
+    Cancelled,
+    #[error("connection cap exceeded for peer")]
+    ConnectionCapExceeded,
+    #[error("geoip database error: {0}")]
+    GeoIp(String),
+    #[error("roster file error: {0}")]
+    Roster(String),
+    #[error("metadata registry error: {0}")]
+    Registry(String),
+    #[error("quorum not met: {0}")]
+    QuorumNotMet(String),
+    #[error("timed out waiting for the allow list to reach the requested peer count")]
+    NotReady,
+}
@@ -0,0 +1,175 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::PeerProviderError;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// GeoInfo is the ASN/country enrichment looked up for a peer's p2p IP, see
+/// `SuiNodeProvider::set_geoip_database`.
+#[derive(Hash, Debug, Clone, PartialEq, Eq)]
+pub struct GeoInfo {
+    pub country: String,
+    pub asn: u32,
+    pub asn_org: String,
+}
+
+/// which IP family a `GeoRange` was parsed from, so `lookup` never compares a v4 address's
+/// numeric value against a v6 range (or vice versa) purely because they collide as `u128`s.
+#[derive(PartialEq, Eq)]
+enum AddressFamily {
+    V4,
+    V6,
+}
+
+/// one parsed row of the database: the inclusive IP range `[start, end]` this entry covers.
+struct GeoRange {
+    family: AddressFamily,
+    start: u128,
+    end: u128,
+    info: GeoInfo,
+}
+
+/// GeoIpDatabase is an in-memory table of IP ranges to `GeoInfo`, loaded from a small CSV file
+/// (see `load`). This isn't a MaxMind-compatible reader; it's a deliberately minimal, dependency-free
+/// format so the feature doesn't require vendoring a GeoIP library or database parser, matching how
+/// `AllowListBloom` and `decode_network_pubkey_bytes` avoid pulling in a dependency for a narrow need.
+pub struct GeoIpDatabase {
+    ranges: Vec<GeoRange>,
+}
+
+impl GeoIpDatabase {
+    /// load parses the CSV file at `path`, one range per line:
+    /// `start_ip,end_ip,country,asn,asn_org`. Blank lines and lines starting with `#` are
+    /// skipped. `start_ip`/`end_ip` may be IPv4 or IPv6; mixing families across rows is fine,
+    /// `lookup` only matches a queried IP against ranges of the same family.
+    pub fn load(path: &Path) -> Result<Self, PeerProviderError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| PeerProviderError::GeoIp(error.to_string()))?;
+        Self::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Self, PeerProviderError> {
+        let mut ranges = Vec::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').collect();
+            let [start, end, country, asn, asn_org] = fields.as_slice() else {
+                return Err(PeerProviderError::GeoIp(format!(
+                    "expected 5 comma-separated fields, got {}: {line}",
+                    fields.len()
+                )));
+            };
+            let start: IpAddr = start
+                .trim()
+                .parse()
+                .map_err(|error| PeerProviderError::GeoIp(format!("bad start ip {start}: {error}")))?;
+            let end: IpAddr = end
+                .trim()
+                .parse()
+                .map_err(|error| PeerProviderError::GeoIp(format!("bad end ip {end}: {error}")))?;
+            let family = address_family(start);
+            if address_family(end) != family {
+                return Err(PeerProviderError::GeoIp(format!(
+                    "start and end ip must be the same address family: {line}"
+                )));
+            }
+            let asn: u32 = asn
+                .trim()
+                .parse()
+                .map_err(|error| PeerProviderError::GeoIp(format!("bad asn {asn}: {error}")))?;
+            ranges.push(GeoRange {
+                family,
+                start: ip_to_u128(start),
+                end: ip_to_u128(end),
+                info: GeoInfo {
+                    country: country.trim().to_owned(),
+                    asn,
+                    asn_org: asn_org.trim().to_owned(),
+                },
+            });
+        }
+        Ok(Self { ranges })
+    }
+
+    /// lookup returns the enrichment for the range containing `ip`, or `None` if no loaded range
+    /// covers it. Ranges are searched linearly; databases bundled with this proxy are expected to
+    /// be small, curated lists (e.g. a handful of sanctioned-region blocks), not a full commercial
+    /// GeoIP table, so this is never a hot path worth indexing.
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoInfo> {
+        let family = address_family(ip);
+        let needle = ip_to_u128(ip);
+        self.ranges
+            .iter()
+            .find(|range| range.family == family && range.start <= needle && needle <= range.end)
+            .map(|range| range.info.clone())
+    }
+}
+
+fn address_family(ip: IpAddr) -> AddressFamily {
+    match ip {
+        IpAddr::V4(_) => AddressFamily::V4,
+        IpAddr::V6(_) => AddressFamily::V6,
+    }
+}
+
+fn ip_to_u128(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(v4) => u32::from(v4) as u128,
+        IpAddr::V6(v6) => u128::from(v6),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_DB: &str = "\
+# country, asn enrichment for a handful of test ranges
+203.0.113.0,203.0.113.255,US,64512,Example LLC
+198.51.100.0,198.51.100.255,DE,64513,Beispiel GmbH
+";
+
+    #[test]
+    fn lookup_enriches_a_known_ip_in_a_bundled_test_database() {
+        let db = GeoIpDatabase::parse(TEST_DB).unwrap();
+
+        let info = db.lookup("203.0.113.42".parse().unwrap()).unwrap();
+
+        assert_eq!(info.country, "US");
+        assert_eq!(info.asn, 64512);
+        assert_eq!(info.asn_org, "Example LLC");
+    }
+
+    #[test]
+    fn lookup_returns_none_for_an_ip_outside_every_range() {
+        let db = GeoIpDatabase::parse(TEST_DB).unwrap();
+
+        assert!(db.lookup("192.0.2.1".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_a_malformed_row() {
+        let error = GeoIpDatabase::parse("not,enough,fields").unwrap_err();
+
+        assert!(matches!(error, PeerProviderError::GeoIp(_)));
+    }
+
+    #[test]
+    fn parse_rejects_a_row_mixing_address_families() {
+        let error = GeoIpDatabase::parse("1.0.0.0,::1:0:0,US,64512,Example LLC").unwrap_err();
+
+        assert!(matches!(error, PeerProviderError::GeoIp(_)));
+    }
+
+    #[test]
+    fn lookup_does_not_match_an_ipv6_address_that_collides_numerically_with_an_ipv4_range() {
+        // 1.0.0.0 and ::100:0 are both 16777216 as a bare u128; an IPv4-only range shouldn't
+        // match an IPv6 query that happens to share that numeric value.
+        let db = GeoIpDatabase::parse("1.0.0.0,1.0.0.255,US,64512,Example LLC").unwrap();
+
+        assert!(db.lookup("::100:0".parse().unwrap()).is_none());
+    }
+}
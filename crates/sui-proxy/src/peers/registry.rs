@@ -0,0 +1,116 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::PeerProviderError;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// RegistryMetadata is out-of-band enrichment about a validator that the chain itself doesn't
+/// report — contact, region, tier — looked up by `sui_address` and merged onto the matching
+/// `SuiPeer` during `extract`, see `SuiNodeProvider::set_metadata_registry`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RegistryMetadata {
+    pub contact: Option<String>,
+    pub region: Option<String>,
+    pub tier: Option<String>,
+}
+
+/// MetadataRegistry is an in-memory table of `RegistryMetadata`, keyed by `sui_address`, loaded
+/// from a small CSV file (see `load`). Mirrors `geoip::GeoIpDatabase`: a deliberately minimal,
+/// dependency-free format rather than pulling in a config-file parser for a narrow need.
+pub struct MetadataRegistry {
+    entries: HashMap<String, RegistryMetadata>,
+}
+
+impl MetadataRegistry {
+    /// load parses the CSV file at `path`, one entry per line: `sui_address,contact,region,tier`.
+    /// Blank lines and lines starting with `#` are skipped. Any of `contact`/`region`/`tier` may
+    /// be left empty to mean "not set" for that address.
+    pub fn load(path: &Path) -> Result<Self, PeerProviderError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| PeerProviderError::Registry(error.to_string()))?;
+        Self::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Self, PeerProviderError> {
+        let mut entries = HashMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').collect();
+            let [sui_address, contact, region, tier] = fields.as_slice() else {
+                return Err(PeerProviderError::Registry(format!(
+                    "expected 4 comma-separated fields, got {}: {line}",
+                    fields.len()
+                )));
+            };
+            let non_empty = |value: &str| {
+                let value = value.trim();
+                (!value.is_empty()).then(|| value.to_owned())
+            };
+            entries.insert(
+                sui_address.trim().to_owned(),
+                RegistryMetadata {
+                    contact: non_empty(contact),
+                    region: non_empty(region),
+                    tier: non_empty(tier),
+                },
+            );
+        }
+        Ok(Self { entries })
+    }
+
+    /// get returns the metadata registered for `sui_address`, or `None` if the registry has no
+    /// entry for it — the peer keeps its default (unset) metadata in that case.
+    pub fn get(&self, sui_address: &str) -> Option<RegistryMetadata> {
+        self.entries.get(sui_address).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_REGISTRY: &str = "\
+# sui_address, contact, region, tier
+0xabc,ops@example.com,us-east,gold
+0xdef,,eu-west,
+";
+
+    #[test]
+    fn get_returns_the_entry_for_a_matching_address() {
+        let registry = MetadataRegistry::parse(TEST_REGISTRY).unwrap();
+
+        let metadata = registry.get("0xabc").unwrap();
+
+        assert_eq!(metadata.contact.as_deref(), Some("ops@example.com"));
+        assert_eq!(metadata.region.as_deref(), Some("us-east"));
+        assert_eq!(metadata.tier.as_deref(), Some("gold"));
+    }
+
+    #[test]
+    fn get_leaves_unset_columns_as_none() {
+        let registry = MetadataRegistry::parse(TEST_REGISTRY).unwrap();
+
+        let metadata = registry.get("0xdef").unwrap();
+
+        assert_eq!(metadata.contact, None);
+        assert_eq!(metadata.region.as_deref(), Some("eu-west"));
+        assert_eq!(metadata.tier, None);
+    }
+
+    #[test]
+    fn get_returns_none_for_an_address_with_no_entry() {
+        let registry = MetadataRegistry::parse(TEST_REGISTRY).unwrap();
+
+        assert!(registry.get("0x999").is_none());
+    }
+
+    #[test]
+    fn parse_rejects_a_malformed_row() {
+        let error = MetadataRegistry::parse("not,enough").unwrap_err();
+
+        assert!(matches!(error, PeerProviderError::Registry(_)));
+    }
+}
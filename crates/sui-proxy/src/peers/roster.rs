@@ -0,0 +1,73 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::PeerProviderError;
+use fastcrypto::ed25519::Ed25519PublicKey;
+use fastcrypto::traits::ToFromBytes;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// parse_roster_file reads `path` as a plain-text list of hex-encoded ed25519 network public
+/// keys, one per line. Blank lines and lines starting with `#` are skipped. This is deliberately
+/// the same kind of small, dependency-free format `GeoIpDatabase::load` uses for its CSV file,
+/// rather than reusing the proxy's own YAML config shape: an operator's roster is just a set of
+/// keys, with no need for the structure a config file carries.
+pub fn parse_roster_file(path: &Path) -> Result<HashSet<Ed25519PublicKey>, PeerProviderError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|error| PeerProviderError::Roster(error.to_string()))?;
+    parse_roster(&contents)
+}
+
+fn parse_roster(contents: &str) -> Result<HashSet<Ed25519PublicKey>, PeerProviderError> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let bytes = hex::decode(line)
+                .map_err(|error| PeerProviderError::Roster(format!("bad hex key {line}: {error}")))?;
+            Ed25519PublicKey::from_bytes(&bytes)
+                .map_err(|error| PeerProviderError::Roster(format!("bad public key {line}: {error}")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::ed25519::Ed25519KeyPair;
+    use fastcrypto::traits::KeyPair;
+
+    #[test]
+    fn parse_roster_skips_blank_lines_and_comments() {
+        let key = Ed25519KeyPair::generate(&mut rand::thread_rng())
+            .public()
+            .to_owned();
+        let contents = format!(
+            "\
+# this is the canary roster
+{}
+
+# trailing comment
+",
+            hex::encode(key.as_bytes())
+        );
+
+        let roster = parse_roster(&contents).unwrap();
+
+        assert_eq!(roster, HashSet::from([key]));
+    }
+
+    #[test]
+    fn parse_roster_rejects_a_non_hex_line() {
+        let error = parse_roster("not-hex").unwrap_err();
+
+        assert!(matches!(error, PeerProviderError::Roster(_)));
+    }
+
+    #[test]
+    fn parse_roster_rejects_a_key_of_the_wrong_length() {
+        let error = parse_roster("aabb").unwrap_err();
+
+        assert!(matches!(error, PeerProviderError::Roster(_)));
+    }
+}
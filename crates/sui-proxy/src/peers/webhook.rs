@@ -0,0 +1,328 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::peers::{PeerChangeEvent, PeerChangeRecord};
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
+
+/// the maximum number of permanently-failed batches retained for operator inspection, see
+/// `WebhookSink::dead_letters`.
+const DEAD_LETTER_CAPACITY: usize = 256;
+
+/// the ceiling `run_webhook_sink` backs off to between retries of one batch, regardless of how
+/// many attempts have already failed.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// WebhookConfig configures an optional sink that POSTs batches of `PeerChangeEvent`s to `url` as
+/// they're recorded to the audit log, see `SuiNodeProvider::set_webhook`.
+#[derive(Clone, Debug)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// events recorded within this window of the first one in a batch are coalesced into a
+    /// single POST, rather than firing one request per change.
+    pub batch_window: Duration,
+    /// the number of retry attempts made for one batch after its first delivery attempt fails,
+    /// before it's given up on and moved to the dead-letter log.
+    pub max_retries: u32,
+    /// the delay before the first retry; doubled after every subsequent failed attempt, capped
+    /// at `MAX_RETRY_BACKOFF`.
+    pub retry_backoff: Duration,
+}
+
+/// WebhookSink owns the dead-letter log for batches that exhausted `WebhookConfig::max_retries`,
+/// shared between the running `run_webhook_sink` task and `SuiNodeProvider::webhook_dead_letters`.
+#[derive(Clone)]
+pub(crate) struct WebhookSink {
+    dead_letters: Arc<RwLock<VecDeque<Vec<PeerChangeEvent>>>>,
+}
+
+impl WebhookSink {
+    pub(crate) fn new() -> Self {
+        Self {
+            dead_letters: Arc::new(RwLock::new(VecDeque::with_capacity(DEAD_LETTER_CAPACITY))),
+        }
+    }
+
+    pub(crate) fn dead_letters(&self) -> Vec<Vec<PeerChangeEvent>> {
+        self.dead_letters.read().unwrap().iter().cloned().collect()
+    }
+
+    fn record_dead_letter(&self, batch: Vec<PeerChangeEvent>) {
+        let mut dead_letters = self.dead_letters.write().unwrap();
+        if dead_letters.len() == DEAD_LETTER_CAPACITY {
+            dead_letters.pop_front();
+        }
+        dead_letters.push_back(batch);
+    }
+}
+
+/// run_webhook_sink batches `PeerChangeRecord`s broadcast on `peer_change_rx` within
+/// `config.batch_window` of the first one into a single JSON array POST to `config.url`,
+/// retrying a failed delivery up to `config.max_retries` times with doubling backoff before
+/// giving up and recording the batch to `sink`'s dead-letter log. Exits once `shutdown` is
+/// cancelled or the broadcast channel closes (the provider it belongs to was dropped).
+pub(crate) async fn run_webhook_sink(
+    mut peer_change_rx: broadcast::Receiver<PeerChangeRecord>,
+    config: WebhookConfig,
+    client: reqwest::Client,
+    sink: WebhookSink,
+    shutdown: CancellationToken,
+) {
+    'outer: loop {
+        let first = tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            result = peer_change_rx.recv() => result,
+        };
+        let first = match first {
+            Ok(record) => record,
+            Err(broadcast::error::RecvError::Closed) => break,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!(
+                    "webhook sink lagged behind the peer-change broadcast by {skipped} events; \
+                     continuing from the next one"
+                );
+                continue;
+            }
+        };
+
+        let mut batch = vec![PeerChangeEvent::from(&first)];
+        let deadline = tokio::time::sleep(config.batch_window);
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break 'outer,
+                _ = &mut deadline => break,
+                result = peer_change_rx.recv() => match result {
+                    Ok(record) => batch.push(PeerChangeEvent::from(&record)),
+                    Err(broadcast::error::RecvError::Closed) => break 'outer,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => warn!(
+                        "webhook sink lagged behind the peer-change broadcast by {skipped} \
+                         events while batching; continuing with what was already collected"
+                    ),
+                },
+            }
+        }
+
+        deliver_with_retries(&client, &config, batch, &sink, &shutdown).await;
+    }
+}
+
+/// deliver_with_retries POSTs `batch` to `config.url`, retrying on either a transport error or a
+/// non-success status up to `config.max_retries` times with doubling backoff, then falls back to
+/// `sink.record_dead_letter` if every attempt failed.
+async fn deliver_with_retries(
+    client: &reqwest::Client,
+    config: &WebhookConfig,
+    batch: Vec<PeerChangeEvent>,
+    sink: &WebhookSink,
+    shutdown: &CancellationToken,
+) {
+    let mut backoff = config.retry_backoff;
+    for attempt in 0..=config.max_retries {
+        match client.post(&config.url).json(&batch).send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => warn!(
+                "webhook delivery attempt {} of {} to {} returned status {}",
+                attempt + 1,
+                config.max_retries + 1,
+                config.url,
+                response.status()
+            ),
+            Err(error) => warn!(
+                "webhook delivery attempt {} of {} to {} failed: {error}",
+                attempt + 1,
+                config.max_retries + 1,
+                config.url
+            ),
+        }
+        if attempt == config.max_retries {
+            break;
+        }
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => return,
+            _ = tokio::time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+    }
+    error!(
+        "webhook delivery to {} permanently failed after {} attempt(s); moving {} event(s) to \
+         the dead-letter log",
+        config.url,
+        config.max_retries + 1,
+        batch.len()
+    );
+    sink.record_dead_letter(batch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peers::PeerChangeKind;
+    use fastcrypto::ed25519::Ed25519KeyPair;
+    use fastcrypto::traits::KeyPair;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::SystemTime;
+
+    fn sample_record() -> PeerChangeRecord {
+        let public_key = Ed25519KeyPair::generate(&mut rand::thread_rng())
+            .public()
+            .to_owned();
+        PeerChangeRecord {
+            public_key,
+            name: "node-a".into(),
+            sui_address: "0x0".into(),
+            epoch: 0,
+            kind: PeerChangeKind::Added,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    /// spawns a server that always fails the first `fail_times` requests with a 500, then
+    /// succeeds, recording how many requests it saw and the bodies it received.
+    async fn spawn_flaky_webhook_server(
+        fail_times: usize,
+    ) -> (String, Arc<AtomicUsize>, Arc<RwLock<Vec<String>>>) {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Response, Server};
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let bodies: Arc<RwLock<Vec<String>>> = Arc::new(RwLock::new(Vec::new()));
+        let attempts_clone = attempts.clone();
+        let bodies_clone = bodies.clone();
+
+        let make_svc = make_service_fn(move |_| {
+            let attempts = attempts_clone.clone();
+            let bodies = bodies_clone.clone();
+            async move {
+                Ok::<_, std::convert::Infallible>(service_fn(move |req| {
+                    let attempts = attempts.clone();
+                    let bodies = bodies.clone();
+                    async move {
+                        let seen = attempts.fetch_add(1, Ordering::SeqCst);
+                        let body_bytes = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                        bodies
+                            .write()
+                            .unwrap()
+                            .push(String::from_utf8(body_bytes.to_vec()).unwrap());
+                        if seen < fail_times {
+                            Ok::<_, std::convert::Infallible>(
+                                Response::builder().status(500).body(Body::empty()).unwrap(),
+                            )
+                        } else {
+                            Ok(Response::new(Body::empty()))
+                        }
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_svc);
+        let addr = server.local_addr();
+        tokio::spawn(async move {
+            let _ = server.await;
+        });
+        (format!("http://{addr}"), attempts, bodies)
+    }
+
+    #[tokio::test]
+    async fn run_webhook_sink_delivers_a_batched_event() {
+        let (url, attempts, bodies) = spawn_flaky_webhook_server(0).await;
+        let (peer_change_tx, peer_change_rx) = broadcast::channel(16);
+        let sink = WebhookSink::new();
+        let shutdown = CancellationToken::new();
+
+        let task = tokio::spawn(run_webhook_sink(
+            peer_change_rx,
+            WebhookConfig {
+                url,
+                batch_window: Duration::from_millis(20),
+                max_retries: 2,
+                retry_backoff: Duration::from_millis(10),
+            },
+            reqwest::Client::new(),
+            sink.clone(),
+            shutdown.clone(),
+        ));
+
+        peer_change_tx.send(sample_record()).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.cancel();
+        task.await.unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        let delivered: serde_json::Value =
+            serde_json::from_str(&bodies.read().unwrap()[0]).unwrap();
+        assert_eq!(delivered.as_array().unwrap().len(), 1);
+        assert!(sink.dead_letters().is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_webhook_sink_retries_a_failed_delivery_before_succeeding() {
+        let (url, attempts, _bodies) = spawn_flaky_webhook_server(2).await;
+        let (peer_change_tx, peer_change_rx) = broadcast::channel(16);
+        let sink = WebhookSink::new();
+        let shutdown = CancellationToken::new();
+
+        let task = tokio::spawn(run_webhook_sink(
+            peer_change_rx,
+            WebhookConfig {
+                url,
+                batch_window: Duration::from_millis(5),
+                max_retries: 3,
+                retry_backoff: Duration::from_millis(5),
+            },
+            reqwest::Client::new(),
+            sink.clone(),
+            shutdown.clone(),
+        ));
+
+        peer_change_tx.send(sample_record()).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        shutdown.cancel();
+        task.await.unwrap();
+
+        assert_eq!(
+            attempts.load(Ordering::SeqCst),
+            3,
+            "should have failed twice then succeeded on the third attempt"
+        );
+        assert!(sink.dead_letters().is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_webhook_sink_dead_letters_a_batch_that_exhausts_every_retry() {
+        let (url, attempts, _bodies) = spawn_flaky_webhook_server(usize::MAX).await;
+        let (peer_change_tx, peer_change_rx) = broadcast::channel(16);
+        let sink = WebhookSink::new();
+        let shutdown = CancellationToken::new();
+
+        let task = tokio::spawn(run_webhook_sink(
+            peer_change_rx,
+            WebhookConfig {
+                url,
+                batch_window: Duration::from_millis(5),
+                max_retries: 2,
+                retry_backoff: Duration::from_millis(5),
+            },
+            reqwest::Client::new(),
+            sink.clone(),
+            shutdown.clone(),
+        ));
+
+        peer_change_tx.send(sample_record()).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        shutdown.cancel();
+        task.await.unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3, "one attempt plus two retries");
+        let dead_letters = sink.dead_letters();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].len(), 1);
+    }
+}
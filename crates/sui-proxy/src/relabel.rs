@@ -0,0 +1,194 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small Prometheus-style relabeling engine applied in `consumer::convert_to_remote_write`,
+//! configured via `RemoteWriteConfig::relabel_rules`. Rules run in the order they're configured,
+//! each seeing the labels left by the ones before it (including the `host`/`network` labels
+//! `consumer::populate_labels` attaches from the reporting peer), mirroring Prometheus's own
+//! `relabel_configs` evaluation order.
+
+use prometheus::proto::{LabelPair, Metric, MetricFamily};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// RelabelAction is what a `RelabelRule` does once its regex matches (or fails to, for `Keep`)
+/// against the joined `source_labels` values.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RelabelAction {
+    /// drop the metric if the regex matches
+    Drop,
+    /// drop the metric unless the regex matches
+    Keep,
+    /// set `target_label` to `replacement` if the regex matches; `replacement` may reference the
+    /// regex's capture groups (e.g. `$1`)
+    Replace,
+}
+
+/// RelabelRule is one Prometheus-style relabeling rule, configured via
+/// `RemoteWriteConfig::relabel_rules` and applied in order by `apply_relabel_rules`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RelabelRule {
+    /// label names whose values, joined with `;`, form the string `regex` is matched against
+    pub source_labels: Vec<String>,
+    /// compiled fresh on every `apply_relabel_rules` call; a rule with an invalid regex is
+    /// logged and skipped rather than failing the whole relay
+    pub regex: String,
+    pub action: RelabelAction,
+    /// the label `RelabelAction::Replace` writes; required for that action, ignored otherwise
+    #[serde(default)]
+    pub target_label: Option<String>,
+    /// the value `RelabelAction::Replace` writes; required for that action, ignored otherwise
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+fn label_value<'a>(labels: &'a [LabelPair], name: &str) -> &'a str {
+    labels
+        .iter()
+        .find(|label| label.get_name() == name)
+        .map(LabelPair::get_value)
+        .unwrap_or_default()
+}
+
+fn source_value(labels: &[LabelPair], source_labels: &[String]) -> String {
+    source_labels
+        .iter()
+        .map(|name| label_value(labels, name))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn set_label(metric: &mut Metric, name: &str, value: String) {
+    if let Some(label) = metric.mut_label().iter_mut().find(|label| label.get_name() == name) {
+        label.set_value(value);
+        return;
+    }
+    let mut label = LabelPair::default();
+    label.set_name(name.to_owned());
+    label.set_value(value);
+    metric.mut_label().push(label);
+}
+
+/// apply_relabel_rules evaluates `rules` against every metric in `data`, in order. A `Drop`/`Keep`
+/// rule that doesn't keep a metric short-circuits the rest of the rules for it; a `Replace` rule
+/// mutates labels in place and evaluation continues, so a later rule sees its effect.
+pub fn apply_relabel_rules(data: &mut [MetricFamily], rules: &[RelabelRule]) {
+    if rules.is_empty() {
+        return;
+    }
+    for mf in data.iter_mut() {
+        let mut kept = Vec::new();
+        'metric: for mut metric in mf.take_metric() {
+            for rule in rules {
+                let regex = match regex::Regex::new(&rule.regex) {
+                    Ok(regex) => regex,
+                    Err(error) => {
+                        warn!("skipping relabel rule with invalid regex {:?}: {error}", rule.regex);
+                        continue;
+                    }
+                };
+                let value = source_value(metric.get_label(), &rule.source_labels);
+                let matched = regex.is_match(&value);
+                match rule.action {
+                    RelabelAction::Drop if matched => continue 'metric,
+                    RelabelAction::Keep if !matched => continue 'metric,
+                    RelabelAction::Replace if matched => {
+                        if let Some(target_label) = &rule.target_label {
+                            let replacement = regex
+                                .replace(&value, rule.replacement.as_deref().unwrap_or(""))
+                                .into_owned();
+                            set_label(&mut metric, target_label, replacement);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            kept.push(metric);
+        }
+        mf.set_metric(protobuf::RepeatedField::from_vec(kept));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prom_to_mimir::tests::{create_labels, create_metric_counter, create_metric_family};
+    use protobuf::RepeatedField;
+
+    #[test]
+    fn apply_relabel_rules_replaces_a_label_for_a_known_peer() {
+        let metric = create_metric_counter(
+            RepeatedField::from_vec(create_labels(vec![("host", "node-a"), ("network", "testnet")])),
+            Default::default(),
+        );
+        let mut data = vec![create_metric_family(
+            "uptime",
+            "help text",
+            None,
+            RepeatedField::from_vec(vec![metric]),
+        )];
+
+        let rules = vec![RelabelRule {
+            source_labels: vec!["host".into()],
+            regex: "node-a".into(),
+            action: RelabelAction::Replace,
+            target_label: Some("validator".into()),
+            replacement: Some("node-a-relabeled".into()),
+        }];
+
+        apply_relabel_rules(&mut data, &rules);
+
+        let labels = data[0].get_metric()[0].get_label();
+        let validator = labels
+            .iter()
+            .find(|label| label.get_name() == "validator")
+            .expect("expected the replace rule to have added the validator label");
+        assert_eq!(validator.get_value(), "node-a-relabeled");
+    }
+
+    #[test]
+    fn apply_relabel_rules_drops_a_metric_matching_a_drop_rule() {
+        let metric = create_metric_counter(
+            RepeatedField::from_vec(create_labels(vec![("host", "node-a")])),
+            Default::default(),
+        );
+        let mut data = vec![create_metric_family(
+            "uptime",
+            "help text",
+            None,
+            RepeatedField::from_vec(vec![metric]),
+        )];
+
+        let rules = vec![RelabelRule {
+            source_labels: vec!["host".into()],
+            regex: "node-a".into(),
+            action: RelabelAction::Drop,
+            target_label: None,
+            replacement: None,
+        }];
+
+        apply_relabel_rules(&mut data, &rules);
+
+        assert!(data[0].get_metric().is_empty());
+    }
+
+    #[test]
+    fn apply_relabel_rules_is_a_no_op_with_no_configured_rules() {
+        let metric = create_metric_counter(
+            RepeatedField::from_vec(create_labels(vec![("host", "node-a")])),
+            Default::default(),
+        );
+        let mut data = vec![create_metric_family(
+            "uptime",
+            "help text",
+            None,
+            RepeatedField::from_vec(vec![metric]),
+        )];
+
+        apply_relabel_rules(&mut data, &[]);
+
+        assert_eq!(data[0].get_metric().len(), 1);
+    }
+}
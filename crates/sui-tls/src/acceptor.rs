@@ -7,7 +7,11 @@ use axum_server::{
     tls_rustls::{RustlsAcceptor, RustlsConfig},
 };
 use fastcrypto::ed25519::Ed25519PublicKey;
-use std::{io, sync::Arc};
+use std::{
+    io,
+    sync::Arc,
+    time::SystemTime,
+};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_rustls::server::TlsStream;
 use tower_layer::Layer;
@@ -17,6 +21,10 @@ pub struct TlsConnectionInfo {
     sni_hostname: Option<Arc<str>>,
     peer_certificates: Option<Arc<[rustls::Certificate]>>,
     public_key: Option<Ed25519PublicKey>,
+    /// when the TLS handshake for this connection completed, for callers that want to enforce a
+    /// maximum session age independent of allow-list membership (e.g.
+    /// `SuiNodeProvider::session_expired` in sui-proxy).
+    established_at: SystemTime,
 }
 
 impl TlsConnectionInfo {
@@ -31,6 +39,10 @@ impl TlsConnectionInfo {
     pub fn public_key(&self) -> Option<&Ed25519PublicKey> {
         self.public_key.as_ref()
     }
+
+    pub fn established_at(&self) -> SystemTime {
+        self.established_at
+    }
 }
 
 /// An `Acceptor` that will provide `TlsConnectionInfo` as an axum `Extension` for use in handlers.
@@ -61,24 +73,32 @@ where
     fn accept(&self, stream: I, service: S) -> Self::Future {
         let acceptor = self.inner.clone();
 
-        Box::pin(async move {
-            let (stream, service) = acceptor.accept(stream, service).await?;
-            let server_conn = stream.get_ref().1;
+        // scopes `LAST_VERIFIED_KEY` to this one connection's task, so
+        // `CertVerifier::verify_client_cert` and `AllowlistBoundSessionStore::put` (both invoked
+        // while processing this connection's handshake) agree on which key a newly cached session
+        // belongs to without the two connections' bindings racing each other.
+        Box::pin(crate::verifier::LAST_VERIFIED_KEY.scope(
+            std::cell::RefCell::new(None),
+            async move {
+                let (stream, service) = acceptor.accept(stream, service).await?;
+                let server_conn = stream.get_ref().1;
 
-            let public_key = if let Some([peer_certificate, ..]) = server_conn.peer_certificates() {
-                crate::certgen::public_key_from_certificate(peer_certificate).ok()
-            } else {
-                None
-            };
+                let public_key = if let Some([peer_certificate, ..]) = server_conn.peer_certificates() {
+                    crate::certgen::public_key_from_certificate(peer_certificate).ok()
+                } else {
+                    None
+                };
 
-            let tls_connect_info = TlsConnectionInfo {
-                peer_certificates: server_conn.peer_certificates().map(From::from),
-                sni_hostname: server_conn.sni_hostname().map(From::from),
-                public_key,
-            };
-            let service = Extension(tls_connect_info).layer(service);
+                let tls_connect_info = TlsConnectionInfo {
+                    peer_certificates: server_conn.peer_certificates().map(From::from),
+                    sni_hostname: server_conn.sni_hostname().map(From::from),
+                    public_key,
+                    established_at: SystemTime::now(),
+                };
+                let service = Extension(tls_connect_info).layer(service);
 
-            Ok((stream, service))
-        })
+                Ok((stream, service))
+            },
+        ))
     }
 }
@@ -0,0 +1,85 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lets the server identity `CertVerifier::rustls_server_config_with_signing_key` uses be backed
+//! by a PKCS#11/HSM key handle instead of a private key loaded from a file: the `Pkcs11Session`
+//! trait is the boundary a real PKCS#11 backend (e.g. built on the `cryptoki` crate) implements
+//! against, so the private key material never has to leave the module. `SoftwarePkcs11Stub` is a
+//! software stand-in for that boundary used by tests; it does not talk to any real HSM.
+
+use fastcrypto::ed25519::Ed25519KeyPair;
+use fastcrypto::traits::{KeyPair, Signer as _, ToFromBytes};
+use rustls::sign::{Signer, SigningKey};
+use rustls::{SignatureAlgorithm, SignatureScheme};
+use std::sync::Arc;
+
+/// Pkcs11Session abstracts a signing key handle held open against a PKCS#11-compatible HSM: only
+/// `sign` crosses the boundary, the private key itself never does. A real backend implements this
+/// against a PKCS#11 session/slot, e.g. via the `cryptoki` crate's `Session::sign`.
+pub trait Pkcs11Session: Send + Sync {
+    /// signs `message` with the session's ed25519 key, returning the raw 64-byte signature.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rustls::Error>;
+}
+
+/// Pkcs11SigningKey adapts a `Pkcs11Session` handle to rustls's `sign::SigningKey`, so
+/// `CertVerifier::rustls_server_config_with_signing_key` can hand rustls a server identity backed
+/// by an HSM. Only ed25519 is supported, matching the rest of this crate's validator identity
+/// scheme.
+#[derive(Clone)]
+pub struct Pkcs11SigningKey {
+    session: Arc<dyn Pkcs11Session>,
+}
+
+impl Pkcs11SigningKey {
+    pub fn new(session: Arc<dyn Pkcs11Session>) -> Self {
+        Self { session }
+    }
+}
+
+impl SigningKey for Pkcs11SigningKey {
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<dyn Signer>> {
+        offered.contains(&SignatureScheme::ED25519).then(|| {
+            Box::new(Pkcs11Signer {
+                session: self.session.clone(),
+            }) as Box<dyn Signer>
+        })
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::ED25519
+    }
+}
+
+struct Pkcs11Signer {
+    session: Arc<dyn Pkcs11Session>,
+}
+
+impl Signer for Pkcs11Signer {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rustls::Error> {
+        self.session.sign(message)
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        SignatureScheme::ED25519
+    }
+}
+
+/// SoftwarePkcs11Stub simulates a PKCS#11 slot holding an ed25519 key, for exercising
+/// `Pkcs11SigningKey`/`CertVerifier::rustls_server_config_with_signing_key` in tests without real
+/// HSM hardware. It wraps an in-memory keypair directly rather than going through a PKCS#11 C API;
+/// a real deployment implements `Pkcs11Session` against its HSM vendor's module instead.
+pub struct SoftwarePkcs11Stub {
+    keypair: Ed25519KeyPair,
+}
+
+impl SoftwarePkcs11Stub {
+    pub fn new(keypair: Ed25519KeyPair) -> Self {
+        Self { keypair }
+    }
+}
+
+impl Pkcs11Session for SoftwarePkcs11Stub {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rustls::Error> {
+        Ok(self.keypair.sign(message).as_bytes().to_vec())
+    }
+}
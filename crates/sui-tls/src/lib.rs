@@ -3,13 +3,19 @@
 
 mod acceptor;
 mod certgen;
+mod hsm;
 mod verifier;
 
 pub const SUI_VALIDATOR_SERVER_NAME: &str = "sui";
 
 pub use acceptor::{TlsAcceptor, TlsConnectionInfo};
 pub use certgen::SelfSignedCertificate;
-pub use verifier::{AllowAll, Allower, CertVerifier, HashSetAllow, ValidatorAllowlist};
+pub use hsm::{Pkcs11Session, Pkcs11SigningKey, SoftwarePkcs11Stub};
+pub use verifier::{
+    AllowAll, Allower, CertError, CertValidationPolicy, CertVerifier, ClientAuthPolicy,
+    HashSetAllow, ValidatorAllowlist,
+};
+pub use verifier::{public_key_from_certificate, validate_cert_chain};
 
 pub use rustls;
 
@@ -125,6 +131,43 @@ mod tests {
             .unwrap_err();
     }
 
+    #[tokio::test]
+    async fn allowlist_bound_session_store_rejects_resumption_after_the_key_leaves_the_allow_list()
+    {
+        use crate::verifier::{AllowlistBoundSessionStore, LAST_VERIFIED_KEY};
+        use rustls::server::StoresServerSessions;
+
+        let mut rng = rand::thread_rng();
+        let keypair = Ed25519KeyPair::generate(&mut rng);
+        let public_key = keypair.public().to_owned();
+
+        let mut allowlist = HashSetAllow::new();
+        allowlist.inner_mut().write().unwrap().insert(public_key.clone());
+
+        let store = AllowlistBoundSessionStore::new(allowlist.clone());
+        let id = vec![1, 2, 3, 4];
+        let value = vec![5, 6, 7, 8];
+
+        // simulates `CertVerifier::verify_client_cert` confirming `public_key`, then the
+        // handshake completing and rustls caching the resulting session, all within one
+        // connection's task scope, as `TlsAcceptor::accept` sets up for a real connection.
+        LAST_VERIFIED_KEY
+            .scope(std::cell::RefCell::new(Some(public_key.clone())), async {
+                assert!(store.put(id.clone(), value.clone()));
+            })
+            .await;
+
+        // the key is still allow-listed: resumption succeeds
+        assert_eq!(store.get(&id), Some(value.clone()));
+
+        // the key leaves the allow list
+        allowlist.inner_mut().write().unwrap().clear();
+
+        // resumption for the since-revoked key is rejected, forcing a fresh handshake (and a
+        // fresh `verify_client_cert` call) instead of silently honoring the old session
+        assert_eq!(store.get(&id), None);
+    }
+
     #[tokio::test]
     async fn axum_acceptor() {
         use fastcrypto::ed25519::Ed25519KeyPair;
@@ -184,4 +227,136 @@ mod tests {
         let body = res.text().await.unwrap();
         assert_eq!(client_public_key.to_string(), body);
     }
+
+    #[tokio::test]
+    async fn optional_client_auth_lets_a_certless_client_complete_the_handshake() {
+        use fastcrypto::ed25519::Ed25519KeyPair;
+        use fastcrypto::traits::KeyPair;
+
+        let mut rng = rand::thread_rng();
+        let client_keypair = Ed25519KeyPair::generate(&mut rng);
+        let client_public_key = client_keypair.public().to_owned();
+        let client_certificate =
+            SelfSignedCertificate::new(client_keypair.private(), SUI_VALIDATOR_SERVER_NAME);
+        let server_keypair = Ed25519KeyPair::generate(&mut rng);
+        let server_certificate = SelfSignedCertificate::new(server_keypair.private(), "localhost");
+
+        let mut allowlist = HashSetAllow::new();
+        allowlist
+            .inner_mut()
+            .write()
+            .unwrap()
+            .insert(client_public_key.clone());
+        let tls_config = CertVerifier::new(allowlist)
+            .with_optional_client_auth()
+            .rustls_server_config(
+                vec![server_certificate.rustls_certificate()],
+                server_certificate.rustls_private_key(),
+            )
+            .unwrap();
+
+        async fn handler(tls_info: axum::Extension<TlsConnectionInfo>) -> String {
+            tls_info
+                .public_key()
+                .map(|key| key.to_string())
+                .unwrap_or_else(|| "anonymous".into())
+        }
+
+        let app = axum::Router::new().route("/", axum::routing::get(handler));
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let server_address = listener.local_addr().unwrap();
+        let acceptor = TlsAcceptor::new(tls_config);
+        let _server = tokio::spawn(async move {
+            axum_server::Server::from_tcp(listener)
+                .acceptor(acceptor)
+                .serve(app.into_make_service())
+                .await
+                .unwrap()
+        });
+
+        let server_url = format!("https://localhost:{}", server_address.port());
+
+        // no client certificate presented at all: the handshake still completes
+        let anonymous_client = reqwest::Client::builder()
+            .add_root_certificate(server_certificate.reqwest_certificate())
+            .https_only(true)
+            .build()
+            .unwrap();
+        let res = anonymous_client.get(&server_url).send().await.unwrap();
+        assert_eq!(res.text().await.unwrap(), "anonymous");
+
+        // a client presenting an allow-listed certificate is still identified as usual
+        let identified_client = reqwest::Client::builder()
+            .add_root_certificate(server_certificate.reqwest_certificate())
+            .identity(client_certificate.reqwest_identity())
+            .https_only(true)
+            .build()
+            .unwrap();
+        let res = identified_client.get(&server_url).send().await.unwrap();
+        assert_eq!(res.text().await.unwrap(), client_public_key.to_string());
+    }
+
+    #[tokio::test]
+    async fn axum_acceptor_with_hsm_backed_signing_key() {
+        use fastcrypto::ed25519::Ed25519KeyPair;
+        use fastcrypto::traits::KeyPair;
+        use std::sync::Arc;
+
+        let mut rng = rand::thread_rng();
+        let client_keypair = Ed25519KeyPair::generate(&mut rng);
+        let client_public_key = client_keypair.public().to_owned();
+        let client_certificate =
+            SelfSignedCertificate::new(client_keypair.private(), SUI_VALIDATOR_SERVER_NAME);
+
+        // the server's certificate is generated the usual way, but its private key is never handed
+        // to rustls: `Pkcs11SigningKey` only ever asks the stub to sign, mirroring how a real
+        // PKCS#11/HSM-backed key would be used.
+        let server_keypair = Ed25519KeyPair::generate(&mut rng);
+        let server_certificate =
+            SelfSignedCertificate::new(server_keypair.copy().private(), "localhost");
+        let signing_key = Arc::new(Pkcs11SigningKey::new(Arc::new(SoftwarePkcs11Stub::new(
+            server_keypair,
+        ))));
+
+        let client = reqwest::Client::builder()
+            .add_root_certificate(server_certificate.reqwest_certificate())
+            .identity(client_certificate.reqwest_identity())
+            .https_only(true)
+            .build()
+            .unwrap();
+
+        let allowlist = HashSetAllow::new();
+        allowlist
+            .inner()
+            .write()
+            .unwrap()
+            .insert(client_public_key.clone());
+        let tls_config = CertVerifier::new(allowlist)
+            .rustls_server_config_with_signing_key(
+                vec![server_certificate.rustls_certificate()],
+                signing_key,
+            )
+            .unwrap();
+
+        async fn handler(tls_info: axum::Extension<TlsConnectionInfo>) -> String {
+            tls_info.public_key().unwrap().to_string()
+        }
+
+        let app = axum::Router::new().route("/", axum::routing::get(handler));
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let server_address = listener.local_addr().unwrap();
+        let acceptor = TlsAcceptor::new(tls_config);
+        let _server = tokio::spawn(async move {
+            axum_server::Server::from_tcp(listener)
+                .acceptor(acceptor)
+                .serve(app.into_make_service())
+                .await
+                .unwrap()
+        });
+
+        let server_url = format!("https://localhost:{}", server_address.port());
+        let res = client.get(&server_url).send().await.unwrap();
+        let body = res.text().await.unwrap();
+        assert_eq!(client_public_key.to_string(), body);
+    }
 }
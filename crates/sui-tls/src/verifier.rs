@@ -2,12 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use fastcrypto::ed25519::Ed25519PublicKey;
-use fastcrypto::traits::ToFromBytes;
 use std::{
-    collections::HashSet,
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
     sync::{Arc, RwLock},
 };
 
+tokio::task_local! {
+    /// the public key `CertVerifier::verify_client_cert` most recently confirmed within this
+    /// connection's task, consulted by `AllowlistBoundSessionStore::put` to bind a newly cached
+    /// TLS session to the key it was established for. Scoped per-connection by
+    /// `TlsAcceptor::accept`; unset (and so ignored) outside that scope, e.g. in tests that call
+    /// `verify_client_cert` directly.
+    pub(crate) static LAST_VERIFIED_KEY: RefCell<Option<Ed25519PublicKey>>;
+}
+
 static SUPPORTED_SIG_ALGS: &[&webpki::SignatureAlgorithm] = &[&webpki::ED25519];
 
 pub type ValidatorAllowlist = Arc<RwLock<HashSet<Ed25519PublicKey>>>;
@@ -59,42 +68,203 @@ impl Allower for HashSetAllow {
     }
 }
 
+/// ClientAuthPolicy controls whether presenting a TLS client certificate is required to complete
+/// the handshake at all, see `CertVerifier::with_optional_client_auth`. Orthogonal to whether a
+/// cert that IS presented passes verification: that's still enforced against the allow list
+/// either way, this only controls whether presenting one is mandatory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientAuthPolicy {
+    /// the handshake fails outright for a client that doesn't present a certificate, the
+    /// historical behavior and `CertVerifier::new`'s default.
+    Required,
+    /// the handshake can complete without a certificate; `TlsConnectionInfo::public_key` is then
+    /// `None` downstream. For servers that expose a mix of protected and public (e.g.
+    /// health-check) routes and enforce mTLS per-route at the HTTP layer instead.
+    Optional,
+}
+
 /// A `rustls::server::ClientCertVerifier` that will ensure that every client provides a valid,
 /// expected certificate and that the client's public key is in the validator set.
 #[derive(Clone, Debug)]
 pub struct CertVerifier<A> {
     allower: A,
+    client_auth_policy: ClientAuthPolicy,
 }
 
 impl<A> CertVerifier<A> {
     pub fn new(allower: A) -> Self {
-        Self { allower }
+        Self {
+            allower,
+            client_auth_policy: ClientAuthPolicy::Required,
+        }
+    }
+
+    /// with_optional_client_auth switches this verifier's handshake-level policy to
+    /// `ClientAuthPolicy::Optional`, see there for what that changes.
+    pub fn with_optional_client_auth(mut self) -> Self {
+        self.client_auth_policy = ClientAuthPolicy::Optional;
+        self
     }
 }
 
-impl<A: Allower + 'static> CertVerifier<A> {
+impl<A: Allower + Clone + 'static> CertVerifier<A> {
     pub fn rustls_server_config(
         self,
         certificates: Vec<rustls::Certificate>,
         private_key: rustls::PrivateKey,
     ) -> Result<rustls::ServerConfig, rustls::Error> {
+        let signing_key = rustls::sign::any_supported_type(&private_key)?;
+        self.rustls_server_config_with_signing_key(certificates, signing_key)
+    }
+
+    /// rustls_server_config_with_signing_key is `rustls_server_config` for a server identity that
+    /// isn't loaded from a file: `signing_key` only needs to produce signatures, so it can be
+    /// backed by a key handle that never exposes the private key material itself, e.g. a
+    /// PKCS#11/HSM session (see `hsm::Pkcs11SigningKey`). `rustls_server_config` is the file-based
+    /// special case of this, converting a raw `rustls::PrivateKey` via
+    /// `rustls::sign::any_supported_type` and delegating here.
+    pub fn rustls_server_config_with_signing_key(
+        self,
+        certificates: Vec<rustls::Certificate>,
+        signing_key: Arc<dyn rustls::sign::SigningKey>,
+    ) -> Result<rustls::ServerConfig, rustls::Error> {
+        let session_storage: Arc<dyn rustls::server::StoresServerSessions> =
+            Arc::new(AllowlistBoundSessionStore::new(self.allower.clone()));
+        let certified_key = Arc::new(rustls::sign::CertifiedKey::new(certificates, signing_key));
         let mut config = rustls::ServerConfig::builder()
             .with_safe_defaults()
             .with_client_cert_verifier(std::sync::Arc::new(self))
-            .with_single_cert(certificates, private_key)?;
+            .with_cert_resolver(Arc::new(SingleCertResolver(certified_key)));
         config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        // session resumption skips `verify_client_cert` entirely, so without this a peer removed
+        // from the allow list after establishing a session could keep riding it; binding each
+        // cached session to the key it was verified for lets us reject a resumption attempt for a
+        // since-revoked key, forcing a fresh handshake (and a fresh `verify_client_cert` call).
+        config.session_storage = session_storage;
 
         Ok(config)
     }
 }
 
+/// SingleCertResolver always resolves to the one `CertifiedKey` it was built with, regardless of
+/// the client's SNI/signature-scheme offer. This crate only ever serves one identity per
+/// `ServerConfig`, so there's nothing to pick between; `with_cert_resolver` is used instead of the
+/// simpler `with_single_cert` purely to accept a pre-built `SigningKey`, not to support multiple
+/// certificates.
+struct SingleCertResolver(Arc<rustls::sign::CertifiedKey>);
+
+impl rustls::server::ResolvesServerCert for SingleCertResolver {
+    fn resolve(&self, _client_hello: rustls::server::ClientHello) -> Option<Arc<rustls::sign::CertifiedKey>> {
+        Some(self.0.clone())
+    }
+}
+
+/// how many sessions `AllowlistBoundSessionStore::bindings` tracks at once, matching the capacity
+/// of the `ServerSessionMemoryCache` it wraps so the two stay bounded together.
+const SESSION_CACHE_CAPACITY: usize = 256;
+
+/// BoundedBindings caps `AllowlistBoundSessionStore`'s id-to-key map at `capacity` entries,
+/// evicting the oldest insertion once it's exceeded. Without this, a binding recorded on `put`
+/// would outlive the wrapped `ServerSessionMemoryCache`'s own eviction of that same session,
+/// leaking one entry per distinct session id ever seen for the life of the process.
+struct BoundedBindings {
+    by_id: HashMap<Vec<u8>, Ed25519PublicKey>,
+    insertion_order: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+impl BoundedBindings {
+    fn new(capacity: usize) -> Self {
+        Self {
+            by_id: HashMap::new(),
+            insertion_order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn insert(&mut self, id: Vec<u8>, key: Ed25519PublicKey) {
+        if self.by_id.insert(id.clone(), key).is_none() {
+            self.insertion_order.push_back(id);
+        }
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.by_id.remove(&oldest);
+            }
+        }
+    }
+
+    fn get(&self, id: &[u8]) -> Option<Ed25519PublicKey> {
+        self.by_id.get(id).cloned()
+    }
+
+    fn remove(&mut self, id: &[u8]) {
+        self.by_id.remove(id);
+        self.insertion_order.retain(|existing| existing != id);
+    }
+}
+
+/// AllowlistBoundSessionStore wraps rustls's default in-memory session cache, additionally
+/// binding each cached session to the public key it was established for (see
+/// `LAST_VERIFIED_KEY`). A resumption lookup for a key that has since left the allow list is
+/// evicted and reported as a cache miss rather than honored, so `CertVerifier::rustls_server_config`
+/// can offer session resumption without it becoming a way to outlive removal from the allow list.
+pub(crate) struct AllowlistBoundSessionStore<A> {
+    allower: A,
+    inner: Arc<dyn rustls::server::StoresServerSessions>,
+    bindings: RwLock<BoundedBindings>,
+}
+
+impl<A: Allower> AllowlistBoundSessionStore<A> {
+    pub(crate) fn new(allower: A) -> Self {
+        Self {
+            allower,
+            inner: rustls::server::ServerSessionMemoryCache::new(SESSION_CACHE_CAPACITY),
+            bindings: RwLock::new(BoundedBindings::new(SESSION_CACHE_CAPACITY)),
+        }
+    }
+}
+
+impl<A: Allower> rustls::server::StoresServerSessions for AllowlistBoundSessionStore<A> {
+    fn put(&self, id: Vec<u8>, value: Vec<u8>) -> bool {
+        if let Ok(Some(key)) = LAST_VERIFIED_KEY.try_with(|cell| cell.borrow().clone()) {
+            self.bindings.write().unwrap().insert(id.clone(), key);
+        }
+        self.inner.put(id, value)
+    }
+
+    fn get(&self, id: &[u8]) -> Option<Vec<u8>> {
+        if let Some(key) = self.bindings.read().unwrap().get(id) {
+            if !self.allower.allowed(&key) {
+                self.bindings.write().unwrap().remove(id);
+                self.inner.take(id);
+                return None;
+            }
+        }
+        self.inner.get(id)
+    }
+
+    fn take(&self, id: &[u8]) -> Option<Vec<u8>> {
+        let key = self.bindings.read().unwrap().get(id);
+        self.bindings.write().unwrap().remove(id);
+        let value = self.inner.take(id);
+        match key {
+            Some(key) if !self.allower.allowed(&key) => None,
+            _ => value,
+        }
+    }
+
+    fn can_cache(&self) -> bool {
+        self.inner.can_cache()
+    }
+}
+
 impl<A: Allower> rustls::server::ClientCertVerifier for CertVerifier<A> {
     fn offer_client_auth(&self) -> bool {
         true
     }
 
     fn client_auth_mandatory(&self) -> Option<bool> {
-        Some(true)
+        Some(self.client_auth_policy == ClientAuthPolicy::Required)
     }
 
     fn client_auth_root_subjects(&self) -> Option<rustls::DistinguishedNames> {
@@ -144,7 +314,12 @@ impl<A: Allower> rustls::server::ClientCertVerifier for CertVerifier<A> {
             .map_err(|_| rustls::Error::UnsupportedNameType)?;
         cert.verify_is_valid_for_dns_name(dns_nameref)
             .map_err(pki_error)
-            .map(|_| rustls::server::ClientCertVerified::assertion())
+            .map(|_| {
+                // best-effort: absent outside a `TlsAcceptor`-scoped connection task (e.g. in
+                // tests calling this directly), in which case there's no session to bind.
+                let _ = LAST_VERIFIED_KEY.try_with(|cell| *cell.borrow_mut() = Some(public_key.clone()));
+                rustls::server::ClientCertVerified::assertion()
+            })
     }
 }
 
@@ -183,22 +358,116 @@ fn pki_error(error: webpki::Error) -> rustls::Error {
     }
 }
 
-pub(crate) fn public_key_from_certificate(
+/// public_key_from_certificate extracts the embedded ed25519 public key from a DER-encoded
+/// certificate, independent of any chain/expiry validation. Exposed so callers that already have
+/// an allow-listed key to compare against (e.g. `SuiNodeProvider::validate_cert`) don't need to
+/// re-implement the x509 parsing this crate already does for `CertVerifier`. Delegates to
+/// `certgen::public_key_from_certificate`, mapping its `anyhow::Error` into the `rustls::Error`
+/// this crate's callers expect.
+pub fn public_key_from_certificate(
     certificate: &rustls::Certificate,
 ) -> Result<Ed25519PublicKey, rustls::Error> {
+    crate::certgen::public_key_from_certificate(certificate)
+        .map_err(|error| rustls::Error::InvalidCertificateData(error.to_string()))
+}
+
+/// CertValidationPolicy controls how strict `validate_cert_chain` is about the presented chain's
+/// trust relationship. It's orthogonal to whether the presenting key belongs to an expected peer
+/// (that's the caller's job — see `SuiNodeProvider::validate_cert` in sui-proxy, which checks the
+/// key first and only consults this policy for the chain itself).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CertValidationPolicy {
+    /// accept only a self-signed end-entity certificate, the policy `CertVerifier` enforces today
+    #[default]
+    SelfSignedOnly,
+    /// also accept a CA-issued chain, verified against the last entry of `intermediates` acting
+    /// as the trust root
+    AllowCaSigned,
+}
+
+/// CertError reports why `validate_cert_chain` rejected a presented certificate.
+#[derive(Debug, thiserror::Error)]
+pub enum CertError {
+    #[error("certificate is not valid yet")]
+    NotYetValid,
+    #[error("certificate has expired")]
+    Expired,
+    #[error("certificate chain does not satisfy the configured validation policy: {0}")]
+    ChainInvalid(String),
+    #[error("malformed certificate: {0}")]
+    Malformed(String),
+}
+
+/// validate_cert_chain checks that `end_entity` is currently within its validity period and that
+/// its trust relationship with `intermediates` satisfies `policy`. It does not check the
+/// presented key against any particular expected value; callers that also need that should check
+/// `public_key_from_certificate` themselves, as `SuiNodeProvider::validate_cert` does.
+pub fn validate_cert_chain(
+    end_entity: &rustls::Certificate,
+    intermediates: &[rustls::Certificate],
+    now: std::time::SystemTime,
+    policy: CertValidationPolicy,
+) -> Result<(), CertError> {
     use x509_parser::{certificate::X509Certificate, prelude::FromDer};
 
-    let cert = X509Certificate::from_der(certificate.0.as_ref())
-        .map_err(|_| rustls::Error::InvalidCertificateEncoding)?;
-    let spki = cert.1.public_key();
-    let public_key_bytes =
-        <ed25519::pkcs8::PublicKeyBytes as pkcs8::DecodePublicKey>::from_public_key_der(spki.raw)
-            .map_err(|e| {
-            rustls::Error::InvalidCertificateData(format!("invalid ed25519 public key: {e}"))
-        })?;
-
-    let public_key = Ed25519PublicKey::from_bytes(public_key_bytes.as_ref()).map_err(|e| {
-        rustls::Error::InvalidCertificateData(format!("invalid ed25519 public key: {e}"))
-    })?;
-    Ok(public_key)
+    let (_, parsed) = X509Certificate::from_der(end_entity.0.as_ref())
+        .map_err(|error| CertError::Malformed(error.to_string()))?;
+
+    let now_ts = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+    let validity = parsed.validity();
+    if now_ts < validity.not_before.timestamp() {
+        return Err(CertError::NotYetValid);
+    }
+    if now_ts > validity.not_after.timestamp() {
+        return Err(CertError::Expired);
+    }
+
+    let webpki_now =
+        webpki::Time::try_from(now).map_err(|_| CertError::Malformed("system time out of webpki's supported range".into()))?;
+
+    match policy {
+        CertValidationPolicy::SelfSignedOnly => {
+            if parsed.subject() != parsed.issuer() {
+                return Err(CertError::ChainInvalid(
+                    "certificate is not self-signed, which the configured policy requires".into(),
+                ));
+            }
+            let (cert, chain, trustroots) = prepare_for_self_signed(end_entity, intermediates)
+                .map_err(|error| CertError::ChainInvalid(format!("{error:?}")))?;
+            cert.verify_is_valid_tls_client_cert(
+                SUPPORTED_SIG_ALGS,
+                &webpki::TlsClientTrustAnchors(&trustroots),
+                &chain,
+                webpki_now,
+            )
+            .map_err(|error| CertError::ChainInvalid(format!("{error:?}")))?;
+        }
+        CertValidationPolicy::AllowCaSigned => {
+            let Some((root, chain_intermediates)) = intermediates.split_last() else {
+                return Err(CertError::ChainInvalid(
+                    "the AllowCaSigned policy requires at least one intermediate certificate acting as the trust root".into(),
+                ));
+            };
+            let trust_anchor = webpki::TrustAnchor::try_from_cert_der(root.0.as_ref())
+                .map_err(|error| CertError::ChainInvalid(format!("{error:?}")))?;
+            let cert = webpki::EndEntityCert::try_from(end_entity.0.as_ref())
+                .map_err(|error| CertError::Malformed(format!("{error:?}")))?;
+            let chain: Vec<&[u8]> = chain_intermediates
+                .iter()
+                .map(|cert| cert.0.as_ref())
+                .collect();
+            cert.verify_is_valid_tls_client_cert(
+                SUPPORTED_SIG_ALGS,
+                &webpki::TlsClientTrustAnchors(&[trust_anchor]),
+                &chain,
+                webpki_now,
+            )
+            .map_err(|error| CertError::ChainInvalid(format!("{error:?}")))?;
+        }
+    }
+
+    Ok(())
 }